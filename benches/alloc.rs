@@ -0,0 +1,66 @@
+#![feature(test)]
+extern crate test;
+
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use test::Bencher;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+};
+
+use ppar::arc::Vector;
+
+// Count allocations, to compare insert/remove with and without the
+// `pool` feature. Kept in its own bench target, so that the counting
+// allocator does not add to the numbers in other benchmarks.
+struct CountingAlloc;
+
+static N_ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        N_ALLOCS.fetch_add(1, Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[bench]
+#[allow(non_snake_case)]
+fn bench_insert_remove_allocs_100K(b: &mut Bencher) {
+    let seed: u64 = random();
+    println!("bench_insert_remove_allocs_100K seed {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut arr: Vector<u64> = Vector::default();
+    for _ in 0..100_000 {
+        let off = rng.gen::<usize>() % (arr.len() + 1);
+        arr.insert(off, rng.gen::<u64>())
+            .expect("bench_insert_remove_allocs_100K: fail insert");
+    }
+
+    let (start, mut n) = (N_ALLOCS.load(Relaxed), 0);
+    b.iter(|| {
+        let off = rng.gen::<usize>() % arr.len();
+        arr.remove(off)
+            .expect("bench_insert_remove_allocs_100K: fail remove");
+        arr.insert(off, rng.gen::<u64>())
+            .expect("bench_insert_remove_allocs_100K: fail reinsert");
+        n += 1;
+    });
+
+    let allocs = (N_ALLOCS.load(Relaxed) - start) as f64 / (n as f64);
+    println!(
+        "bench_insert_remove_allocs_100K pool:{} allocs/op:{:.2}",
+        cfg!(feature = "pool"),
+        allocs
+    );
+}
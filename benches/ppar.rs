@@ -19,7 +19,7 @@ fn bench_prepend(b: &mut Bencher) {
             .expect("bench_prepend: fail insert")
     });
 
-    let ratio = mem_ratio(8, arr.footprint(), arr.len());
+    let ratio = arr.memory_report().overhead;
     println!("bench_prepend n:{} mem_ratio:{}%", arr.len(), ratio);
 }
 
@@ -35,10 +35,23 @@ fn bench_append(b: &mut Bencher) {
             .expect("bench_append: fail insert")
     });
 
-    let ratio = mem_ratio(8, arr.footprint(), arr.len());
+    let ratio = arr.memory_report().overhead;
     println!("bench_append n:{} mem_ratio:{}%", arr.len(), ratio);
 }
 
+#[bench]
+fn bench_push(b: &mut Bencher) {
+    let seed: u64 = random();
+    println!("bench_push seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut arr: Vector<u64> = Vector::default();
+    b.iter(|| arr.push(rng.gen::<u64>()));
+
+    let ratio = arr.memory_report().overhead;
+    println!("bench_push n:{} mem_ratio:{}%", arr.len(), ratio);
+}
+
 #[bench]
 fn bench_insert_rand(b: &mut Bencher) {
     let seed: u64 = random();
@@ -52,7 +65,7 @@ fn bench_insert_rand(b: &mut Bencher) {
             .expect("bench_insert_rand: fail insert")
     });
 
-    let ratio = mem_ratio(8, arr.footprint(), arr.len());
+    let ratio = arr.memory_report().overhead;
     println!("bench_insert_rand n:{} mem_ratio:{}%", arr.len(), ratio);
 }
 
@@ -74,7 +87,7 @@ fn bench_get_100K(b: &mut Bencher) {
         arr.get(off).expect("bench_get_100K: fail get");
     });
 
-    let ratio = mem_ratio(8, arr.footprint(), arr.len());
+    let ratio = arr.memory_report().overhead;
     println!("bench_get_100K n:{} mem_ratio:{}%", arr.len(), ratio);
 }
 
@@ -97,7 +110,7 @@ fn bench_update_100K(b: &mut Bencher) {
             .expect("bench_update_100K: fail update");
     });
 
-    let ratio = mem_ratio(8, arr.footprint(), arr.len());
+    let ratio = arr.memory_report().overhead;
     println!("bench_update_100K n:{} mem_ratio:{}%", arr.len(), ratio);
 }
 
@@ -122,7 +135,7 @@ fn bench_delete_100K(b: &mut Bencher) {
             .expect("bench_delete_100K: fail reinsert");
     });
 
-    let ratio = mem_ratio(8, arr.footprint(), arr.len());
+    let ratio = arr.memory_report().overhead;
     println!("bench_delete_100K n:{} mem_ratio:{}%", arr.len(), ratio);
 }
 
@@ -145,6 +158,85 @@ fn bench_clone(b: &mut Bencher) {
     });
 }
 
-fn mem_ratio(size: usize, mem: usize, n: usize) -> f64 {
-    ((((mem as f64) / (n as f64)) - (size as f64)) / size as f64) * 100_f64
+#[cfg(feature = "rayon")]
+#[bench]
+#[allow(non_snake_case)]
+fn bench_from_slice_1M(b: &mut Bencher) {
+    let arr: Vec<u64> = (0..1_000_000).collect();
+    b.iter(|| Vector::from_slice(&arr, None));
+}
+
+#[cfg(feature = "rayon")]
+#[bench]
+#[allow(non_snake_case)]
+fn bench_par_from_slice_1M(b: &mut Bencher) {
+    let arr: Vec<u64> = (0..1_000_000).collect();
+    b.iter(|| Vector::par_from_slice(&arr, None));
+}
+
+#[cfg(feature = "rayon")]
+#[bench]
+#[allow(non_snake_case)]
+fn bench_for_each_leaf_mut_1M(b: &mut Bencher) {
+    let vals: Vec<f32> = (0..1_000_000).map(|x| x as f32).collect();
+    let mut arr = Vector::from_slice(&vals, None);
+    b.iter(|| {
+        arr.for_each_leaf_mut(|data| data.iter_mut().for_each(|x| *x = x.sqrt()))
+            .unwrap()
+    });
+}
+
+#[cfg(feature = "rayon")]
+#[bench]
+#[allow(non_snake_case)]
+fn bench_par_for_each_leaf_mut_1M(b: &mut Bencher) {
+    let vals: Vec<f32> = (0..1_000_000).map(|x| x as f32).collect();
+    let mut arr = Vector::from_slice(&vals, None);
+    b.iter(|| {
+        arr.par_for_each_leaf_mut(|data| data.iter_mut().for_each(|x| *x = x.sqrt()))
+            .unwrap()
+    });
+}
+
+// same size as u8, but not Copy, so that leaf nodes are cloned item by item.
+#[derive(Clone)]
+struct Byte(#[allow(dead_code)] u8);
+
+#[bench]
+#[allow(non_snake_case)]
+fn bench_split_off_copy_4M(b: &mut Bencher) {
+    let arr: Vector<u8> = Vector::from_slice(&vec![0xAB; 4 << 20], None);
+    let mut off = 0;
+    b.iter(|| {
+        off = (off + 4099) % arr.len();
+        arr.clone().split_off(off).unwrap()
+    });
+}
+
+#[bench]
+#[allow(non_snake_case)]
+fn bench_split_off_clone_4M(b: &mut Bencher) {
+    let arr: Vector<Byte> = Vector::from_slice(&vec![Byte(0xAB); 4 << 20], None);
+    let mut off = 0;
+    b.iter(|| {
+        off = (off + 4099) % arr.len();
+        arr.clone().split_off(off).unwrap()
+    });
+}
+
+#[bench]
+fn bench_insert_mut_from_slice(b: &mut Bencher) {
+    let seed: u64 = random();
+    println!("bench_insert_mut_from_slice seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let vals: Vec<u64> = (0..1000).collect();
+    b.iter(|| {
+        let mut arr = Vector::from_slice(&vals, None);
+        for _ in 0..10 {
+            let off = rng.gen::<usize>() % (arr.len() + 1);
+            arr.insert_mut(off, 0).unwrap();
+        }
+        arr
+    });
 }
@@ -1,15 +1,26 @@
 //! Module implement thread-safe persistent array.
 
 use std::sync::Arc as Ref;
+use std::sync::Weak;
 
 #[path = "./ppar.rs"]
 mod ppar;
 
+#[cfg(any(feature = "serde", test))]
+pub use self::ppar::LeafInterner;
+#[cfg(any(feature = "ipld", test))]
+pub use self::ppar::MerkleCache;
 /// Persistent array, thread-safe version.
-pub use self::ppar::{IntoIter, Iter, Vector};
+pub use self::ppar::{
+    ChunksExact, IntoIter, Iter, MapCache, MemoryReport, RebalanceEvent, RevIter,
+    UniqueMut, Vector, WindowRef, WindowsRef,
+};
 #[cfg(test)]
 pub use ppar::validate;
 
+/// Callback type for `Vector::set_on_rebalance`.
+pub type RebalanceFn = dyn Fn(RebalanceEvent) + Send + Sync;
+
 impl<T> Vector<T>
 where
     T: Clone,
@@ -23,4 +34,18 @@ where
     pub fn is_rc_type() -> bool {
         false
     }
+
+    /// Convert this vector into a contiguous `Arc<[T]>`, for libraries that
+    /// share slices using `Arc<[T]>`. This is O(n), elements are moved out
+    /// of leaf nodes that are uniquely owned by the vector, and cloned
+    /// otherwise.
+    pub fn into_arc_slice(self) -> std::sync::Arc<[T]> {
+        let mut arr = Vec::with_capacity(self.len());
+        arr.extend(self);
+        Ref::from(arr)
+    }
 }
+
+#[cfg(test)]
+#[path = "arc_test.rs"]
+mod arc_test;
@@ -0,0 +1,24 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use super::*;
+
+#[test]
+fn test_into_arc_slice() {
+    let seed: u64 = random();
+    println!("test_into_arc_slice seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&refv, Some(128));
+
+        // shared with a clone, elements are cloned.
+        let val: std::sync::Arc<[u64]> = arr.clone().into_arc_slice();
+        assert_eq!(&val[..], &refv[..]);
+        validate(&arr, &refv);
+
+        // uniquely owned, elements are moved.
+        let val = arr.into_arc_slice();
+        assert_eq!(&val[..], &refv[..]);
+    }
+}
@@ -125,6 +125,7 @@ where
     IntoIter,
     Iter,
     SplitOff(Index),
+    AppendLeafCap(Index, usize), // (off, leaf_size)
 }
 
 impl<T> Op<T>
@@ -147,6 +148,7 @@ where
             Op::IntoIter => "into_iter",
             Op::Iter => "iter",
             Op::SplitOff(_) => "split_off",
+            Op::AppendLeafCap(_, _) => "append_leaf_cap",
         };
         let val = counts.get(key).map(|v| v + 1).unwrap_or(1);
         counts.insert(key, val);
@@ -187,7 +189,8 @@ macro_rules! fuzzy_ops {
                 mut n_footprint,
                 mut n_into_iter,
                 mut n_iter,
-            ) = (0, 0, 0, 0, 0);
+                mut n_append_leaf_cap,
+            ) = (0, 0, 0, 0, 0, 0);
 
             let mut counts: BTreeMap<&'static str, usize> = BTreeMap::new();
             for _i in 0..n_ops {
@@ -327,6 +330,21 @@ macro_rules! fuzzy_ops {
                         assert!(arr.split_off(off).is_err());
                         true
                     }
+                    // rebuild the right half with a different leaf node size,
+                    // so that append has to rebuild it back.
+                    Op::AppendLeafCap(Index(off), leaf_size)
+                        if n_append_leaf_cap < 5 && off <= arr.len() =>
+                    {
+                        let a: Vec<T> = arr.split_off(off).unwrap().into();
+                        let leaf_size = 64 + (leaf_size % 1024);
+                        let a = crate::$ref::Vector::from_slice(&a, Some(leaf_size));
+                        arr.append(a);
+                        let a: Vec<T> = arr.clone().into();
+                        assert_eq!(a, vec);
+                        n_append_leaf_cap += 1;
+                        true
+                    }
+                    Op::AppendLeafCap(_, _) => false,
                 };
 
                 if ok {
@@ -40,6 +40,25 @@
 //! **`arbitrary`** feature must be enabled, for [arc::Vector] and [rc::Vector]
 //! types to implement the `arbitrary::Arbitrary` trait.
 //!
+//! **`ipld`** feature must be enabled, for [arc::Vector] and [rc::Vector]
+//! types to compute a content-addressed merkle root using SHA-256.
+//!
+//! **`serde`** feature must be enabled, for [arc::Vector] and [rc::Vector]
+//! types to serialize and deserialize their tree structure, refer to
+//! `Vector::serialize_structured` and `Vector::deserialize_structured`.
+//!
+//! **`pool`** feature must be enabled, for [arc::Vector] and [rc::Vector]
+//! types to recycle node allocations across copy-on-write insert and
+//! remove. Nodes shared with other versions of the vector are never recycled.
+//!
+//! **`rayon`** feature must be enabled, for [arc::Vector] and [rc::Vector]
+//! types to build a new vector from a slice in parallel, refer to
+//! `Vector::par_from_slice`.
+//!
+//! **`bytemuck`** feature must be enabled, for [arc::Vector] and [rc::Vector]
+//! types, of plain-old-data elements, to convert to and from raw bytes,
+//! refer to `Vector::to_bytes` and `Vector::from_bytes`.
+//!
 //! Alternate libraries
 //! ===================
 //!
@@ -101,6 +120,9 @@ macro_rules! err_at {
 /// error location.
 pub enum Error {
     IndexFail(String, String),
+    InvalidInput(String, String),
+    Shared(String, String),
+    Fatal(String, String),
 }
 
 impl fmt::Display for Error {
@@ -109,6 +131,9 @@ impl fmt::Display for Error {
 
         match self {
             IndexFail(p, msg) => write!(f, "{} IndexFail: {}", p, msg),
+            InvalidInput(p, msg) => write!(f, "{} InvalidInput: {}", p, msg),
+            Shared(p, msg) => write!(f, "{} Shared: {}", p, msg),
+            Fatal(p, msg) => write!(f, "{} Fatal: {}", p, msg),
         }
     }
 }
@@ -121,7 +146,9 @@ impl fmt::Debug for Error {
 
 impl error::Error for Error {}
 
+#[allow(clippy::duplicate_mod)]
 pub mod arc;
+#[allow(clippy::duplicate_mod)]
 pub mod rc;
 
 /// Leaf node shall not exceed this default size.
@@ -1,4 +1,12 @@
-use std::{borrow::Borrow, mem};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt,
+    iter::{Product, Sum},
+    mem,
+    ops::{self, Bound, RangeBounds},
+};
 
 use super::*;
 use crate::{Error, Result};
@@ -13,6 +21,9 @@ where
     root: Ref<Node<T>>,
     auto_rebalance: bool,
     leaf_cap: usize,
+    pool: NodePool<T>,
+    on_rebalance: RebalanceHook,
+    debug_checks: bool,
 }
 
 impl<T> Clone for Vector<T> {
@@ -22,10 +33,31 @@ impl<T> Clone for Vector<T> {
             root: Ref::clone(&self.root),
             auto_rebalance: self.auto_rebalance,
             leaf_cap: self.leaf_cap,
+            pool: NodePool::default(),
+            on_rebalance: self.on_rebalance.clone(),
+            debug_checks: self.debug_checks,
         }
     }
 }
 
+impl<T, const N: usize> TryFrom<Vector<T>> for [T; N]
+where
+    T: Clone,
+{
+    type Error = Error;
+
+    /// Return `InvalidInput` error if vector's length is not `N`.
+    fn try_from(val: Vector<T>) -> Result<[T; N]> {
+        if val.len != N {
+            err_at!(InvalidInput, msg: "vector length {} != {}", val.len, N)?
+        }
+
+        let mut iter = val.into_iter();
+        // length is checked above.
+        Ok(std::array::from_fn(|_| iter.next().unwrap()))
+    }
+}
+
 impl<T> From<Vector<T>> for Vec<T>
 where
     T: Clone,
@@ -45,6 +77,26 @@ where
     }
 }
 
+impl<T> From<Vec<T>> for Vector<T> {
+    /// Elements are moved into leaf nodes of default size, refer
+    /// [crate::LEAF_CAP].
+    fn from(val: Vec<T>) -> Vector<T> {
+        let mut lb = LeafBuilder::new(crate::LEAF_CAP);
+        val.into_iter().for_each(|item| lb.push(item));
+        lb.build(true)
+    }
+}
+
+impl<'a, T> From<&'a [T]> for Vector<T>
+where
+    T: Clone,
+{
+    /// Same as [Vector::from_slice] with default leaf node size.
+    fn from(val: &'a [T]) -> Vector<T> {
+        Vector::from_slice(val, None)
+    }
+}
+
 impl<T> PartialEq for Vector<T>
 where
     T: PartialEq,
@@ -77,6 +129,184 @@ where
     }
 }
 
+#[cfg(any(feature = "ipld", test))]
+impl<T> Vector<T>
+where
+    T: std::hash::Hash,
+{
+    /// Return the merkle root of the tree, computed using SHA-256. Each leaf
+    /// node is hashed over its item count and items, and each intermediate
+    /// node is hashed as H(left-hash || right-hash || weight).
+    ///
+    /// Items are fed through their `std::hash::Hash` implementation, with
+    /// every integer, including `usize`, `isize` and length prefixes,
+    /// encoded as fixed-width little-endian bytes. So the root is the same
+    /// across 32/64-bit and big/little-endian targets. But the sequence of
+    /// writes made by a `Hash` implementation is not promised to be stable
+    /// across Rust releases, say for `str`, hence for content addressing
+    /// across toolchain upgrades prefer items whose `Hash` is under the
+    /// application's control.
+    ///
+    /// Two vectors with equal elements and same tree shape shall have the
+    /// same merkle root. Vectors with equal elements but different tree
+    /// shape, say after [Self::rebalance], shall have different merkle roots.
+    pub fn merkle_root(&self) -> [u8; 32] {
+        self.root.merkle_hash()
+    }
+
+    /// Same as [Self::merkle_root], except that hashes are looked up from,
+    /// and added to, `cache`. Sub-trees shared with vectors already hashed
+    /// using the same cache are not hashed again.
+    pub fn merkle_root_cached(&self, cache: &mut MerkleCache<T>) -> [u8; 32] {
+        Node::merkle_hash_cached(&self.root, cache)
+    }
+}
+
+#[cfg(any(feature = "serde", test))]
+impl<T> Vector<T> {
+    /// Serialize the vector along with its tree structure, that is, the
+    /// intermediate nodes with their weights and the leaf nodes with their
+    /// items. Use [Self::deserialize_structured] to read it back.
+    pub fn serialize_structured<S>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error>
+    where
+        T: serde::Serialize,
+        S: serde::Serializer,
+    {
+        use serde::Serialize;
+
+        let val = SerVector {
+            len: self.len,
+            leaf_cap: self.leaf_cap,
+            auto_rebalance: self.auto_rebalance,
+            root: SerNode(self.root.as_ref()),
+        };
+        val.serialize(serializer)
+    }
+
+    /// Deserialize a vector serialized using [Self::serialize_structured].
+    /// Leaf nodes are interned using `interner`, so that identical leaf
+    /// nodes across versions of a vector, deserialized with the same
+    /// interner, are shared. Refer to [LeafInterner] before calling `_mut`
+    /// methods on the deserialized vector.
+    pub fn deserialize_structured<'de, D>(
+        deserializer: D,
+        interner: &mut LeafInterner<T>,
+    ) -> std::result::Result<Vector<T>, D::Error>
+    where
+        T: serde::Deserialize<'de> + std::hash::Hash + Eq,
+        D: serde::Deserializer<'de>,
+    {
+        use serde::{de::Error, Deserialize};
+
+        let val: DeVector<T> = DeVector::deserialize(deserializer)?;
+        let size = mem::size_of::<T>();
+        if size == 0 || val.leaf_cap < size {
+            let msg = format!("invalid leaf_cap {} for item size {}", val.leaf_cap, size);
+            return Err(D::Error::custom(msg));
+        }
+
+        let max_items = max_leaf_items::<T>(val.leaf_cap);
+        let (root, n) = val
+            .root
+            .into_node(interner, max_items)
+            .map_err(D::Error::custom)?;
+        if n != val.len {
+            let msg = format!("length mismatch {} != {}", n, val.len);
+            return Err(D::Error::custom(msg));
+        }
+
+        let val = Vector {
+            len: val.len,
+            root,
+            auto_rebalance: val.auto_rebalance,
+            leaf_cap: val.leaf_cap,
+            pool: NodePool::default(),
+            on_rebalance: RebalanceHook::default(),
+            debug_checks: false,
+        };
+        Ok(val)
+    }
+}
+
+#[cfg(any(feature = "bytemuck", test))]
+impl<T> Vector<T>
+where
+    T: bytemuck::Pod,
+{
+    /// Return the elements of this vector as raw bytes, in native
+    /// endianness. Use [Self::from_bytes] to read it back on a machine with
+    /// same endianness. For portable formats use the `serde` feature.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len * mem::size_of::<T>());
+        for (_, data) in self.iter_leaves() {
+            bytes.extend_from_slice(bytemuck::cast_slice(data));
+        }
+        bytes
+    }
+
+    /// Construct a new vector from raw bytes, as returned by [Self::to_bytes].
+    /// `bytes` need not be aligned for `T`. Return `InvalidInput` error if
+    /// length of `bytes` is not a multiple of the size of `T`.
+    pub fn from_bytes(bytes: &[u8], leaf_node_size: Option<usize>) -> Result<Vector<T>> {
+        let size = mem::size_of::<T>();
+        if size == 0 || bytes.len() % size != 0 {
+            err_at!(InvalidInput, msg: "{} bytes for item size {}", bytes.len(), size)?
+        }
+
+        let mut lb = LeafBuilder::new(leaf_node_size.unwrap_or(crate::LEAF_CAP));
+        for chunk in bytes.chunks_exact(size) {
+            lb.push(bytemuck::pod_read_unaligned(chunk));
+        }
+        Ok(lb.build(true))
+    }
+}
+
+impl<T> Vector<Vector<T>>
+where
+    T: Clone,
+{
+    /// Concatenate all the inner vectors into a single balanced vector, in
+    /// the same order. Leaf nodes of inner vectors, having the same leaf
+    /// node size as this vector, are shared as is, so that the cost is in
+    /// the number of leaf nodes rather than the number of elements. Leaf
+    /// nodes that are less than half full, and leaf nodes of inner vectors
+    /// with a different leaf node size, are copied into packed leaf nodes.
+    ///
+    /// Settings, like leaf node size, are taken from this vector.
+    pub fn flatten(self) -> Vector<T> {
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        let half = max_leaf_items::<T>(self.leaf_cap) / 2;
+
+        for (_, data) in self.iter_leaves() {
+            for inner in data.iter() {
+                let leafs = match inner.leaf_cap == self.leaf_cap {
+                    true => {
+                        let root = Ref::clone(&inner.root);
+                        Node::collect_leaf_nodes(root, false, inner.leaf_cap)
+                    }
+                    false => {
+                        inner.iter().for_each(|item| lb.push(item.clone()));
+                        continue;
+                    }
+                };
+                for leaf in leafs.into_iter() {
+                    match leaf.as_ref() {
+                        Node::Z { data } if data.len() < half => {
+                            data.iter().for_each(|item| lb.push(item.clone()))
+                        }
+                        _ => lb.push_leaf(leaf),
+                    }
+                }
+            }
+        }
+
+        lb.build(self.auto_rebalance)
+    }
+}
+
 impl<T> IntoIterator for Vector<T>
 where
     T: Clone,
@@ -87,29 +317,72 @@ where
     fn into_iter(self) -> Self::IntoIter {
         let mut iter = IntoIter {
             stack: Vec::default(),
-            node: None,
-            off: 0,
+            leaf: None,
         };
-        Node::build_into_iter_stack(&self.root, &mut iter);
+        Node::build_into_iter_stack(self.root, &mut iter);
         iter
     }
 }
 
+impl<T> Extend<T> for Vector<T>
+where
+    T: Clone,
+{
+    /// Elements from `iter` are built into a balanced sub-tree and joined
+    /// with this vector, refer to [Vector::append] for details.
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        iter.into_iter().for_each(|item| lb.push(item));
+        let other = lb.build(self.auto_rebalance);
+
+        match (self.len, other.len) {
+            (_, 0) => (),
+            (0, _) => self.replace_tree(other),
+            (_, _) => self.append(other),
+        }
+    }
+}
+
+impl<'a, T> Extend<&'a T> for Vector<T>
+where
+    T: 'a + Copy,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = &'a T>,
+    {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
 impl<T> Default for Vector<T> {
     fn default() -> Vector<T> {
+        Vector::new()
+    }
+}
+
+impl<T> Vector<T>
+where
+    T: Sized,
+{
+    /// Construct a new empty vector, with default leaf size and
+    /// auto-rebalance enabled. Root of an empty vector is an empty leaf
+    /// node, which is allocated, hence this is not a `const fn`.
+    pub fn new() -> Vector<T> {
         Vector {
             len: 0,
             root: Node::empty_leaf(),
             auto_rebalance: true,
             leaf_cap: crate::LEAF_CAP,
+            pool: NodePool::default(),
+            on_rebalance: RebalanceHook::default(),
+            debug_checks: false,
         }
     }
-}
 
-impl<T> Vector<T>
-where
-    T: Sized,
-{
     /// Construct a new vector with an initial array of values.
     pub fn from_slice(slice: &[T], leaf_node_size: Option<usize>) -> Vector<T>
     where
@@ -117,8 +390,14 @@ where
     {
         let n = max_leaf_items::<T>(leaf_node_size.unwrap_or(crate::LEAF_CAP));
 
-        let mut leafs: Vec<Ref<Node<T>>> =
-            slice.chunks(n).map(|x| Ref::new(Node::from(x))).collect();
+        let mut leafs: Vec<Ref<Node<T>>> = slice
+            .chunks(n)
+            .map(|x| {
+                Ref::new(Node::Z {
+                    data: leaf_data(x, n),
+                })
+            })
+            .collect();
         leafs.reverse();
 
         let depth = (leafs.len() as f64).log2().ceil() as usize;
@@ -130,6 +409,85 @@ where
             root,
             auto_rebalance: true,
             leaf_cap: leaf_node_size.unwrap_or(crate::LEAF_CAP),
+            pool: NodePool::default(),
+            on_rebalance: RebalanceHook::default(),
+            debug_checks: false,
+        }
+    }
+
+    /// Construct a new vector of `n` elements, where each element is
+    /// generated by calling `f` with its position. Elements are filled
+    /// directly into leaf nodes, without an intermediate array.
+    pub fn from_fn<F>(n: usize, f: F, leaf_node_size: Option<usize>) -> Vector<T>
+    where
+        F: FnMut(usize) -> T,
+    {
+        let mut lb = LeafBuilder::new(leaf_node_size.unwrap_or(crate::LEAF_CAP));
+        (0..n).map(f).for_each(|item| lb.push(item));
+        lb.build(true)
+    }
+
+    /// Same as [Self::from_slice], except that leaf nodes are copied from
+    /// `slice` in parallel, using rayon's global thread-pool.
+    #[cfg(any(feature = "rayon", test))]
+    pub fn par_from_slice(slice: &[T], leaf_node_size: Option<usize>) -> Vector<T>
+    where
+        T: Clone + Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        let n = max_leaf_items::<T>(leaf_node_size.unwrap_or(crate::LEAF_CAP));
+
+        let datas: Vec<Vec<T>> = slice.par_chunks(n).map(|x| leaf_data(x, n)).collect();
+        let mut leafs: Vec<Ref<Node<T>>> = datas
+            .into_iter()
+            .rev()
+            .map(|data| Ref::new(Node::Z { data }))
+            .collect();
+
+        let depth = (leafs.len() as f64).log2().ceil() as usize;
+        let (root, _) = Node::build_bottoms_up(depth, &mut leafs);
+        debug_assert!(leafs.is_empty());
+
+        Vector {
+            len: slice.len(),
+            root,
+            auto_rebalance: true,
+            leaf_cap: leaf_node_size.unwrap_or(crate::LEAF_CAP),
+            pool: NodePool::default(),
+            on_rebalance: RebalanceHook::default(),
+            debug_checks: false,
+        }
+    }
+
+    /// Construct a new vector using `leafs` as the leaf nodes, in the same
+    /// order. This is the inverse of [Self::into_leaf_vecs]. Leaf nodes are
+    /// used as is, even if they are larger than `leaf_node_size`.
+    pub fn from_leaf_vecs(
+        leafs: Vec<Vec<T>>,
+        leaf_node_size: Option<usize>,
+    ) -> Vector<T> {
+        let len = leafs.iter().map(|data| data.len()).sum();
+
+        let mut leafs: Vec<Ref<Node<T>>> = leafs
+            .into_iter()
+            .filter(|data| !data.is_empty())
+            .map(|data| Ref::new(Node::Z { data }))
+            .collect();
+        leafs.reverse();
+
+        let depth = (leafs.len() as f64).log2().ceil() as usize;
+        let (root, _) = Node::build_bottoms_up(depth, &mut leafs);
+        debug_assert!(leafs.is_empty());
+
+        Vector {
+            len,
+            root,
+            auto_rebalance: true,
+            leaf_cap: leaf_node_size.unwrap_or(crate::LEAF_CAP),
+            pool: NodePool::default(),
+            on_rebalance: RebalanceHook::default(),
+            debug_checks: false,
         }
     }
 
@@ -152,6 +510,41 @@ where
         self.auto_rebalance = rebalance;
         self
     }
+
+    /// Enable expensive checks, that verify the tree's invariants after every
+    /// insert, remove, split_off and append operation, refer to
+    /// [Self::verify]. Disabled by default. Setting is carried over to clones
+    /// of this vector, and to vectors split from this vector.
+    ///
+    /// **causes panic, naming the operation, when an invariant breaks**
+    pub fn set_debug_checks(&mut self, debug_checks: bool) -> &mut Self {
+        self.debug_checks = debug_checks;
+        self
+    }
+
+    /// Set a callback that is called every time the tree is rebuilt, either
+    /// by auto-rebalance or by [Self::rebalance], useful for metering the
+    /// cost of rebalancing. Callback is carried over to clones of this
+    /// vector, and to vectors split from this vector. Pass `None` to remove
+    /// the callback, which is the default.
+    pub fn set_on_rebalance(&mut self, f: Option<Box<RebalanceFn>>) -> &mut Self {
+        self.on_rebalance = RebalanceHook(f.map(Ref::from));
+        self
+    }
+
+    /// Same as [Self::set_leaf_size], but consumes and returns the vector,
+    /// to be used in builder style.
+    pub fn with_leaf_size(mut self, leaf_size: usize) -> Self {
+        self.set_leaf_size(leaf_size);
+        self
+    }
+
+    /// Same as [Self::set_auto_rebalance], but consumes and returns the
+    /// vector, to be used in builder style.
+    pub fn with_auto_rebalance(mut self, rebalance: bool) -> Self {
+        self.set_auto_rebalance(rebalance);
+        self
+    }
 }
 
 impl<T> Vector<T>
@@ -165,27 +558,214 @@ where
         self.len
     }
 
+    /// Return the length of the vector, recomputed from the tree by
+    /// walking its right spine, or `Fatal` error if it doesn't match the
+    /// cached length returned by [Self::len]. Cheaper than [Self::verify],
+    /// which walks the whole tree.
+    pub fn checked_len(&self) -> Result<usize> {
+        match self.root.len() {
+            n if n == self.len => Ok(n),
+            n => err_at!(Fatal, msg: "length mismatch {} != {}", n, self.len),
+        }
+    }
+
     /// Return whether empty vector
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Return the number of items that can be held by the leaf nodes without
+    /// reallocating, that is, the sum of capacity of all leaf nodes.
+    pub fn capacity(&self) -> usize {
+        self.root.capacity()
+    }
+
+    /// Reserve capacity for at least `additional` items to be appended at
+    /// the end of the vector, without reallocating the rightmost leaf node.
+    /// Capacity is reserved only up to the leaf node size, beyond which
+    /// leaf nodes are split anyway.
+    ///
+    /// This is a no-op when the rightmost leaf node is shared with other
+    /// versions of the vector, since it shall be copied on the next write.
+    pub fn reserve(&mut self, additional: usize) {
+        let max_items = max_leaf_items::<T>(self.leaf_cap);
+
+        let mut node = &mut self.root;
+        loop {
+            match Ref::get_mut(node) {
+                Some(Node::M { right, .. }) => node = right,
+                Some(Node::Z { data }) => {
+                    let n = max_items.saturating_sub(data.len());
+                    data.reserve_exact(additional.min(n));
+                    break;
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Return the address of the root node, which identifies this version
+    /// of the vector. Clones of a vector have the same root, while every
+    /// mutation creates a new root.
+    ///
+    /// Address of a dropped root can be reused by a new root, hence keep a
+    /// clone of the vector for as long as its address is compared.
+    pub fn root_ptr(&self) -> *const u8 {
+        Ref::as_ptr(&self.root) as *const u8
+    }
+
+    /// Return whether both vectors share the same root node. This is
+    /// identity, not equality: true implies that both vectors hold the same
+    /// elements, while false does not imply that they differ. Does not
+    /// require `T: PartialEq`.
+    pub fn ptr_eq(&self, other: &Vector<T>) -> bool {
+        Ref::ptr_eq(&self.root, &other.root)
+    }
+
+    /// Apply `f` on this vector and return the new vector, only if this
+    /// vector's root is still `expected_root`, refer to [Self::root_ptr].
+    /// Return `None` otherwise, without calling `f`.
+    pub fn update_if_unchanged<F>(
+        &self,
+        expected_root: *const u8,
+        f: F,
+    ) -> Option<Vector<T>>
+    where
+        F: FnOnce(&Vector<T>) -> Vector<T>,
+    {
+        match self.root_ptr() == expected_root {
+            true => Some(f(self)),
+            false => None,
+        }
+    }
+
     /// Return the memory foot-print for this instance.
     pub fn footprint(&self) -> usize {
         mem::size_of_val(self) + self.root.footprint()
     }
 
+    /// Return a detailed break-up of [Self::footprint], walking the tree
+    /// once. Refer to [MemoryReport] for details.
+    pub fn memory_report(&self) -> MemoryReport {
+        let mut report = MemoryReport {
+            n_leafs: 0,
+            depth: 0,
+            spine_bytes: 0,
+            leaf_bytes: 0,
+            data_bytes: 0,
+            data_capacity_bytes: 0,
+            overhead: 0.0,
+        };
+        self.root.memory_report(1, &mut report);
+
+        let total = mem::size_of_val(self) + report.total();
+        report.overhead = match (self.len, mem::size_of::<T>()) {
+            (0, _) | (_, 0) => 0.0,
+            (n, s) => ((((total as f64) / (n as f64)) - (s as f64)) / s as f64) * 100_f64,
+        };
+        report
+    }
+
+    /// Return the distribution of leaf nodes by how full they are. Fill
+    /// ratio, number of elements in a leaf node over the maximum number of
+    /// elements for configured leaf node size, is binned into `buckets`
+    /// equal-width buckets, and the count of leaf nodes in each bucket is
+    /// returned. Over-full leaf nodes are counted in the last bucket, and
+    /// empty leaf nodes are skipped.
+    ///
+    /// Many leaf nodes in the lower buckets indicate fragmentation, which
+    /// can be fixed by calling [Self::rebalance] with `packed` as true.
+    pub fn leaf_fill_histogram(&self, buckets: usize) -> Vec<usize> {
+        let mut histogram = vec![0; buckets];
+        if buckets == 0 {
+            return histogram;
+        }
+
+        let cap = max_leaf_items::<T>(self.leaf_cap);
+        for (_, data) in self.iter_leaves() {
+            let bucket = (data.len() * buckets) / cap;
+            histogram[bucket.min(buckets - 1)] += 1;
+        }
+        histogram
+    }
+
     /// Return a reference to the element at that position or `IndexFail` error
     /// if out of bounds.
     pub fn get(&self, index: usize) -> Result<&T> {
         if index < self.len {
             Ok(self.root.get(index))
         } else {
-            err_at!(IndexFail, msg: "index {} out of bounds", index)?
+            err_at!(IndexFail, msg: "index {} out of bounds for length {}", index, self.len)?
+        }
+    }
+
+    /// Return all the elements as a single slice, if the vector is made of
+    /// a single leaf node, without copying. Return `None` otherwise, in
+    /// which case elements can be copied using `Vec::from`.
+    pub fn as_contiguous(&self) -> Option<&[T]> {
+        match self.root.as_ref() {
+            Node::Z { data } => Some(data.as_slice()),
+            Node::M { .. } => None,
+        }
+    }
+
+    /// Return elements within `range` as a slice, without copying, if all
+    /// of them fall inside a single leaf node. Return `None` otherwise, or
+    /// if range is out of bounds, in which case use [Self::clone_range] or
+    /// collect from [Self::iter_at].
+    ///
+    /// Whether a range fits within a leaf node depends on the leaf node
+    /// size and on the history of updates to the vector, small ranges are
+    /// more likely to succeed.
+    pub fn get_contiguous_range(&self, range: ops::Range<usize>) -> Option<&[T]> {
+        let (start, end) = (range.start, range.end);
+        if start > end || end > self.len {
+            return None;
+        } else if start == end {
+            return Some(&[]);
+        }
+
+        let (data, off) = self.root.get_leaf(start);
+        data.get(off..(off + end - start))
+    }
+
+    /// Return a reference to the element at that position, along with the
+    /// number of elements remaining after that position, that is
+    /// `len - index - 1`. Return `None` if out of bounds.
+    pub fn get_with_remaining(&self, index: usize) -> Option<(&T, usize)> {
+        match index < self.len {
+            true => Some((self.root.get(index), self.len - index - 1)),
+            false => None,
+        }
+    }
+
+    /// Return a reference to the element at that position, wrapping around
+    /// the vector. Negative and out of bound indices are mapped within
+    /// [0, len) using euclidean modulo, for example -1 maps to the last
+    /// element. Return `None` only if vector is empty.
+    pub fn get_wrapping(&self, index: isize) -> Option<&T> {
+        match self.len {
+            0 => None,
+            n => {
+                let index = index.rem_euclid(n as isize) as usize;
+                Some(self.root.get(index))
+            }
         }
     }
 
+    /// Return a reference to the element at that position, without checking
+    /// for bounds.
+    ///
+    /// # Safety
+    ///
+    /// Caller must guarantee that `index < self.len()`. Calling this method
+    /// with an out-of-bounds index may return an unrelated element or panic.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> &T {
+        self.root.get(index)
+    }
+
     /// Insert an element at `off` position within the vector, or `IndexFail`
     /// error if out of bounds. Call this for copy-on-write insert, especially
     /// when `Vector` is shared among multiple owners. In cases of
@@ -195,17 +775,33 @@ where
     where
         T: Clone,
     {
-        let (root, _) = if off <= self.len {
-            let rn = Rebalance::new(self);
-            self.root.insert(off, value, &rn)?
+        self.insert_depth(off, value).map(|_| ())
+    }
+
+    /// Same as [Self::insert], and return the depth of the path from root to
+    /// the inserted element, counting the root and the leaf node. If the
+    /// insert triggers auto-rebalance, the rebuilt tree's longest path is
+    /// counted instead. This is the depth used to trigger auto-rebalance,
+    /// which applications can use to rebalance the vector themselves when
+    /// auto-rebalance is disabled, instead of walking the whole tree after
+    /// every insert.
+    pub fn insert_depth(&mut self, off: usize, value: T) -> Result<usize>
+    where
+        T: Clone,
+    {
+        let (root, depth) = if off <= self.len {
+            let rn = Rebalance::new(self, &self.on_rebalance);
+            self.root.insert(off, value, &rn, &mut self.pool)?
         } else {
-            err_at!(IndexFail, msg: "index {} out of bounds", off)?
+            err_at!(IndexFail, msg: "index {} out of bounds for length {}", off, self.len)?
         };
 
-        self.root = root;
+        let old = mem::replace(&mut self.root, root);
+        self.pool.recycle(old);
         self.len += 1;
+        self.debug_check("insert");
 
-        Ok(())
+        Ok(depth)
     }
 
     /// Insert an element at `off` position within the vector, or `IndexFail`
@@ -215,66 +811,182 @@ where
     ///
     /// **causes panic when used under shared-ownership**
     pub fn insert_mut(&mut self, off: usize, value: T) -> Result<()>
+    where
+        T: Clone,
+    {
+        self.insert_mut_depth(off, value).map(|_| ())
+    }
+
+    /// Same as [Self::insert_mut], and return the depth of the path from
+    /// root to the inserted element, refer to [Self::insert_depth].
+    ///
+    /// **causes panic when used under shared-ownership**
+    pub fn insert_mut_depth(&mut self, off: usize, value: T) -> Result<usize>
     where
         T: Clone,
     {
         if off <= self.len {
-            let rn = Rebalance::new(self);
+            let rn = Rebalance::new(self, &self.on_rebalance);
 
-            let depth = Ref::get_mut(&mut self.root)
-                .unwrap()
-                .insert_mut(off, value, &rn)?;
+            let depth = Ref::get_mut(&mut self.root).unwrap().insert_mut(
+                off,
+                value,
+                &rn,
+                &mut self.pool,
+            )?;
 
             let packed = false;
             let force = false;
-            let (root, _) =
+            let (root, depth) =
                 Node::auto_rebalance(Ref::clone(&self.root), depth, packed, force, &rn);
 
             self.root = root;
             self.len += 1;
-            Ok(())
+            self.debug_check("insert_mut");
+            Ok(depth)
         } else {
-            err_at!(IndexFail, msg: "index {} out of bounds", off)?
+            err_at!(IndexFail, msg: "index {} out of bounds for length {}", off, self.len)?
         }
     }
 
-    /// Update the element at `off` position within the vector, or `IndexFail`
-    /// error if out of bounds. Call this for copy-on-write update, especially
-    /// when `Vector` is shared among multiple owners. In cases of
-    /// single-ownership use `update_mut`, which does in-place mutation, for
-    /// better performance.
-    pub fn update(&mut self, off: usize, value: T) -> Result<T>
+    /// Append an element to the end of the vector. Optimized for
+    /// append-heavy workloads: when the right-most leaf node has room and
+    /// the path to it is uniquely owned, the element is pushed into that
+    /// leaf node in-place, without copying nodes or updating weights.
+    /// Otherwise it falls back to copy-on-write [Self::insert].
+    pub fn push(&mut self, value: T)
     where
         T: Clone,
     {
-        let (root, val) = if off < self.len {
-            self.root.update(off, value)
-        } else {
-            err_at!(IndexFail, msg: "offset {} out of bounds", off)?
-        };
-
-        self.root = root;
-        Ok(val)
+        let cap = max_leaf_items::<T>(self.leaf_cap);
+        match Node::last_leaf_mut(&mut self.root) {
+            Some(data) if data.len() < cap => {
+                data.push(value);
+                self.len += 1;
+                self.debug_check("push");
+            }
+            // offset is always within bounds.
+            _ => self.insert(self.len, value).unwrap(),
+        }
     }
 
-    /// Update an element at `off` position within the vector, or `IndexFail`
-    /// error if out of bounds. Call this for in-place update and only when
-    /// `Vector` is under single ownership. In cases of shared-ownership
-    /// use `update` api which does copy-on-write.
+    /// Insert all elements from `items` starting at `off` position within
+    /// the vector, or `IndexFail` error if out of bounds. Inserted elements
+    /// are built into a balanced sub-tree and joined with the existing tree,
+    /// which is cheaper than inserting them one by one.
     ///
-    /// **causes panic when used under shared-ownership**
-    pub fn update_mut(&mut self, off: usize, value: T) -> Result<T>
+    /// Optionally, application can call [Self::rebalance] to make the vector
+    /// fully balanced.
+    pub fn insert_many<I>(&mut self, off: usize, items: I) -> Result<()>
     where
         T: Clone,
+        I: IntoIterator<Item = T>,
     {
-        if off < self.len {
-            Ok(Ref::get_mut(&mut self.root).unwrap().update_mut(off, value))
-        } else {
-            err_at!(IndexFail, msg: "offset {} out of bounds", off)
+        if off > self.len {
+            err_at!(IndexFail, msg: "index {} out of bounds for length {}", off, self.len)?
         }
-    }
 
-    /// Remove and return the element at `off` position within the vector,
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        items.into_iter().for_each(|item| lb.push(item));
+        let other = lb.build(self.auto_rebalance);
+
+        if !other.is_empty() {
+            let tail = self.split_off(off)?;
+            self.append(other);
+            if !tail.is_empty() {
+                self.append(tail);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prepend all elements from `items`, in the same order, to the front of
+    /// the vector. Elements are built into a balanced sub-tree and joined
+    /// as the left sub-tree of the new root, which is cheaper than inserting
+    /// them one by one at offset 0.
+    ///
+    /// Optionally, application can call [Self::rebalance] to make the vector
+    /// fully balanced.
+    pub fn extend_front_from_slice(&mut self, items: &[T])
+    where
+        T: Clone,
+    {
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        items.iter().for_each(|item| lb.push(item.clone()));
+        let other = lb.build(self.auto_rebalance);
+
+        if !other.is_empty() {
+            self.root = match self.len {
+                0 => other.root,
+                _ => Node::newm(other.root, Ref::clone(&self.root), other.len),
+            };
+            self.len += other.len;
+        }
+    }
+
+    /// Update the element at `off` position within the vector, or `IndexFail`
+    /// error if out of bounds. Call this for copy-on-write update, especially
+    /// when `Vector` is shared among multiple owners. In cases of
+    /// single-ownership use `update_mut`, which does in-place mutation, for
+    /// better performance.
+    pub fn update(&mut self, off: usize, value: T) -> Result<T>
+    where
+        T: Clone,
+    {
+        let (root, val) = if off < self.len {
+            self.root.update(off, value)
+        } else {
+            err_at!(IndexFail, msg: "offset {} out of bounds for length {}", off, self.len)?
+        };
+
+        self.root = root;
+        Ok(val)
+    }
+
+    /// Update an element at `off` position within the vector, or `IndexFail`
+    /// error if out of bounds. Call this for in-place update and only when
+    /// `Vector` is under single ownership. In cases of shared-ownership
+    /// use `update` api which does copy-on-write.
+    ///
+    /// **causes panic when used under shared-ownership**
+    pub fn update_mut(&mut self, off: usize, value: T) -> Result<T>
+    where
+        T: Clone,
+    {
+        if off < self.len {
+            Ok(Ref::get_mut(&mut self.root).unwrap().update_mut(off, value))
+        } else {
+            err_at!(IndexFail, msg: "offset {} out of bounds for length {}", off, self.len)
+        }
+    }
+
+    /// Update all elements at positions in `updates`, sorted by position, in
+    /// a single walk of the tree, and return the old values in the same
+    /// order. Return `IndexFail` error if any position is out of bounds,
+    /// and `InvalidInput` error if `updates` are not sorted, in which case
+    /// the vector is left unchanged.
+    ///
+    /// Nodes that are uniquely owned are updated in-place, while shared
+    /// nodes are copied once, irrespective of the number of updates
+    /// under them.
+    pub fn update_many(&mut self, updates: &[(usize, T)]) -> Result<Vec<T>>
+    where
+        T: Clone,
+    {
+        if let Some((off, _)) = updates.iter().find(|(off, _)| *off >= self.len) {
+            err_at!(IndexFail, msg: "offset {} out of bounds for length {}", off, self.len)?
+        }
+        if updates.windows(2).any(|w| w[0].0 > w[1].0) {
+            err_at!(InvalidInput, msg: "updates are not sorted by position")?
+        }
+
+        let mut olds = Vec::with_capacity(updates.len());
+        Node::update_many(&mut self.root, updates, 0, &mut olds);
+        Ok(olds)
+    }
+
+    /// Remove and return the element at `off` position within the vector,
     /// or `IndexFail` error if out of bounds. Call this for copy-on-write
     /// remove, especially when `Vector` is shared among multiple owners.
     /// In cases of single-ownership use `remove_mut`, which does in-place
@@ -284,13 +996,15 @@ where
         T: Clone,
     {
         let (root, val) = if off < self.len {
-            self.root.remove(off)
+            self.root.remove(off, &mut self.pool)
         } else {
-            err_at!(IndexFail, msg: "offset {} out of bounds", off)?
+            err_at!(IndexFail, msg: "offset {} out of bounds for length {}", off, self.len)?
         };
 
-        self.root = root;
+        let old = mem::replace(&mut self.root, root);
+        self.pool.recycle(old);
         self.len -= 1;
+        self.debug_check("remove");
         Ok(val)
     }
 
@@ -307,96 +1021,1518 @@ where
         let val = if off < self.len {
             Ref::get_mut(&mut self.root).unwrap().remove_mut(off)
         } else {
-            err_at!(IndexFail, msg: "offset {} out of bounds", off)?
+            err_at!(IndexFail, msg: "offset {} out of bounds for length {}", off, self.len)?
         };
 
         self.len -= 1;
+        self.debug_check("remove_mut");
         Ok(val)
     }
 
-    /// Return an iterator over each element in Vector.
-    pub fn iter(&self) -> Iter<T> {
-        Iter::new(&self.root)
+    /// Insert `value` at `off` position, in-place, like [Self::insert_mut],
+    /// when the path from root to `off` is uniquely owned, otherwise fall
+    /// back to copy-on-write [Self::insert]. Never panics on shared-ownership.
+    ///
+    /// Ownership is checked along the path to `off`, not just at the root,
+    /// since `_mut` methods require every node on that path to be unique.
+    /// The check costs O(depth) and does not copy nodes.
+    pub fn insert_cow(&mut self, off: usize, value: T) -> Result<()>
+    where
+        T: Clone,
+    {
+        match off <= self.len && Node::is_path_unique(&mut self.root, off) {
+            true => self.insert_mut(off, value),
+            false => self.insert(off, value),
+        }
     }
 
-    /// Splits the collection into two at the given index.
-    ///
-    /// Returns a new Vector containing the elements in the range [at, len).
-    /// After the call, the original vector will be left containing the
-    /// elements [0, at) with its previous capacity unchanged.
+    /// Same as [Self::insert_cow], but for [Self::remove_mut] and
+    /// [Self::remove].
+    pub fn remove_cow(&mut self, off: usize) -> Result<T>
+    where
+        T: Clone,
+    {
+        match off < self.len && Node::is_path_unique(&mut self.root, off) {
+            true => self.remove_mut(off),
+            false => self.remove(off),
+        }
+    }
+
+    /// Same as [Self::insert_cow], but for [Self::update_mut] and
+    /// [Self::update].
+    pub fn update_cow(&mut self, off: usize, value: T) -> Result<T>
+    where
+        T: Clone,
+    {
+        match off < self.len && Node::is_path_unique(&mut self.root, off) {
+            true => self.update_mut(off, value),
+            false => self.update(off, value),
+        }
+    }
+
+    /// Check once that the vector is under single ownership, that is every
+    /// node in the tree is uniquely owned, and return a guard for in-place
+    /// mutation. Return `Shared` error if any part of the tree is shared.
     ///
-    /// Optionally, application can call [Self::rebalance] on `self`, and
-    /// the returned vector, to make the vectors fully balanced.
-    pub fn split_off(&mut self, off: usize) -> Result<Vector<T>>
+    /// `_mut` methods on the guard don't panic, since no new sharing can
+    /// happen while the guard holds this vector.
+    pub fn as_unique_mut(&mut self) -> Result<UniqueMut<'_, T>> {
+        match Node::is_unique(&mut self.root) {
+            true => Ok(UniqueMut { arr: self }),
+            false => err_at!(Shared, msg: "vector is shared"),
+        }
+    }
+
+    /// Return mutable references to the elements at positions `i` and `j`,
+    /// in that order. Return `IndexFail` error if either of them is out of
+    /// bounds, `InvalidInput` error if `i == j`, and `Shared` error if the
+    /// path to either of the elements is shared with other versions of the
+    /// vector.
+    pub fn get_disjoint_mut(&mut self, i: usize, j: usize) -> Result<(&mut T, &mut T)> {
+        if i >= self.len || j >= self.len {
+            err_at!(IndexFail, msg: "index {},{} out of bounds for length {}", i, j, self.len)?
+        } else if i == j {
+            err_at!(InvalidInput, msg: "index {} is not disjoint", i)?
+        }
+
+        let root = match Ref::get_mut(&mut self.root) {
+            Some(root) => root,
+            None => err_at!(Shared, msg: "vector is shared")?,
+        };
+        if i < j {
+            root.get_disjoint_mut(i, j)
+        } else {
+            let (b, a) = root.get_disjoint_mut(j, i)?;
+            Ok((a, b))
+        }
+    }
+
+    /// Call `f` with each leaf node's data as a mutable slice, in order, for
+    /// in-place bulk transformation. Return `Shared` error if any part of
+    /// the tree is shared with other versions of the vector, in which case
+    /// `f` is never called.
+    pub fn for_each_leaf_mut<F>(&mut self, mut f: F) -> Result<()>
+    where
+        F: FnMut(&mut [T]),
+    {
+        if Node::is_unique(&mut self.root) {
+            Ref::get_mut(&mut self.root)
+                .unwrap()
+                .for_each_leaf_mut(&mut f);
+            Ok(())
+        } else {
+            err_at!(Shared, msg: "vector is shared")
+        }
+    }
+
+    /// Same as [Self::for_each_leaf_mut], except that leaf nodes are passed
+    /// to `f` in parallel, using rayon's global thread-pool, hence in no
+    /// particular order.
+    #[cfg(any(feature = "rayon", test))]
+    pub fn par_for_each_leaf_mut<F>(&mut self, f: F) -> Result<()>
+    where
+        T: Send,
+        F: Fn(&mut [T]) + Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        if Node::is_unique(&mut self.root) {
+            let mut leafs = vec![];
+            Ref::get_mut(&mut self.root)
+                .unwrap()
+                .collect_leafs_mut(&mut leafs);
+            leafs.into_par_iter().for_each(f);
+            Ok(())
+        } else {
+            err_at!(Shared, msg: "vector is shared")
+        }
+    }
+
+    /// Convert the vector into its leaf nodes, in order, each leaf node as
+    /// a block of contiguous items. Leaf nodes that are uniquely owned by
+    /// this vector are moved out, shared ones are cloned. Empty leaf nodes
+    /// are skipped.
+    pub fn into_leaf_vecs(self) -> Vec<Vec<T>>
     where
         T: Clone,
     {
-        let val = match off {
-            off if off > self.len => {
-                err_at!(IndexFail, msg: "offset {} out of bounds", off)?
+        Node::collect_leaf_nodes(self.root, false, self.leaf_cap)
+            .into_iter()
+            .filter(|leaf| leaf.len() > 0)
+            .map(|leaf| match Ref::try_unwrap(leaf) {
+                Ok(Node::Z { data }) => data,
+                Ok(_) => unreachable!(),
+                Err(leaf) => leaf.cow().into_data(),
+            })
+            .collect()
+    }
+
+    /// Compare this vector with `other`, element by element, using `f` as
+    /// the equality function. Vectors sharing the same root node are
+    /// treated as equal without calling `f`, hence `f` must be reflexive.
+    pub fn eq_by<F>(&self, other: &Vector<T>, mut f: F) -> bool
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        if self.len != other.len {
+            false
+        } else if Ref::ptr_eq(&self.root, &other.root) {
+            true
+        } else {
+            self.iter().zip(other.iter()).all(|(a, b)| f(a, b))
+        }
+    }
+
+    /// Remove all but the first of consecutive elements in the vector that
+    /// resolve to the same key. Like [Vec::dedup_by_key], if the vector is
+    /// sorted by key, this removes all duplicates.
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        T: Clone,
+        K: PartialEq,
+        F: FnMut(&T) -> K,
+    {
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        let mut last: Option<K> = None;
+        for (_, data) in self.iter_leaves() {
+            for item in data.iter() {
+                let k = key(item);
+                if last.as_ref() != Some(&k) {
+                    lb.push(item.clone());
+                    last = Some(k);
+                }
             }
-            off if off == self.len => Vector {
-                len: 0,
-                root: Node::empty_leaf(),
-                auto_rebalance: self.auto_rebalance,
-                leaf_cap: self.leaf_cap,
-            },
-            off => {
-                let (node, root, n) = self.root.split_off(off, self.len);
-                self.root = node;
-                self.len -= n;
-                Vector {
-                    len: n,
-                    root,
-                    auto_rebalance: self.auto_rebalance,
-                    leaf_cap: self.leaf_cap,
+        }
+        let val = lb.build(self.auto_rebalance);
+        self.replace_tree(val);
+    }
+
+    /// Retain only the elements for which `f` returns true, in the same
+    /// order, and return the number of elements removed. Kept elements are
+    /// rebuilt into a new tree with packed leaf nodes.
+    pub fn retain<F>(&mut self, mut f: F) -> usize
+    where
+        T: Clone,
+        F: FnMut(&T) -> bool,
+    {
+        let n = self.len;
+        self.retain_indexed(|_, item| f(item));
+        n - self.len
+    }
+
+    /// Same as [Self::retain], except that `f` can mutate the elements it
+    /// keeps. Return the number of elements removed.
+    pub fn retain_mut<F>(&mut self, mut f: F) -> usize
+    where
+        T: Clone,
+        F: FnMut(&mut T) -> bool,
+    {
+        let n = self.len;
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        for mut item in self.iter().cloned() {
+            if f(&mut item) {
+                lb.push(item);
+            }
+        }
+        let val = lb.build(self.auto_rebalance);
+        self.replace_tree(val);
+        n - self.len
+    }
+
+    /// Retain only the elements for which `f` returns true, in the same
+    /// order. `f` is called with the element's position in the vector, as
+    /// it was before the call, along with the element. Kept elements are
+    /// rebuilt into a new tree with packed leaf nodes.
+    pub fn retain_indexed<F>(&mut self, mut f: F)
+    where
+        T: Clone,
+        F: FnMut(usize, &T) -> bool,
+    {
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        for (off, data) in self.iter_leaves() {
+            for (i, item) in data.iter().enumerate() {
+                if f(off + i, item) {
+                    lb.push(item.clone());
                 }
             }
+        }
+        let val = lb.build(self.auto_rebalance);
+        self.replace_tree(val);
+    }
+
+    /// Return an iterator over clones of each element in Vector, without
+    /// consuming the vector. Use [IntoIterator::into_iter] to move elements
+    /// out of the vector.
+    pub fn cloned_iter(&self) -> impl Iterator<Item = T> + '_
+    where
+        T: Clone,
+    {
+        self.iter().cloned()
+    }
+
+    /// Return an iterator over each element in Vector, from the last
+    /// element to the first.
+    pub fn rev_iter(&self) -> RevIter<'_, T> {
+        RevIter::new(&self.root)
+    }
+
+    /// Return a new vector after merging adjacent elements using `f`, like
+    /// itertools' `coalesce`. `f` is called with each pair of adjacent
+    /// elements, and shall return either `Ok` with the merged element, which
+    /// is then paired with the next element, or `Err` with both elements
+    /// unchanged. Elements are streamed across leaf nodes, hence elements
+    /// on either side of a leaf boundary are merged as well.
+    pub fn coalesce<F>(&self, mut f: F) -> Vector<T>
+    where
+        T: Clone,
+        F: FnMut(T, T) -> std::result::Result<T, (T, T)>,
+    {
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        let mut last: Option<T> = None;
+        for item in self.iter().cloned() {
+            last = match last.take() {
+                None => Some(item),
+                Some(prev) => match f(prev, item) {
+                    Ok(item) => Some(item),
+                    Err((prev, item)) => {
+                        lb.push(prev);
+                        Some(item)
+                    }
+                },
+            };
+        }
+        if let Some(item) = last {
+            lb.push(item)
+        }
+        lb.build(self.auto_rebalance)
+    }
+
+    /// Return an iterator over each element in Vector.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.root)
+    }
+
+    /// Return an iterator over each element in Vector, starting from the
+    /// element at `index`. Iterator is positioned in O(log n) time, which
+    /// makes it cheap to resume iteration from a saved index. If `index` is
+    /// out of bounds, returned iterator is empty.
+    pub fn iter_at(&self, index: usize) -> Iter<'_, T> {
+        Iter::new_at(&self.root, index.min(self.len))
+    }
+
+    /// Return a reference to the minimum element in the vector, or `None` if
+    /// vector is empty. If several elements are equally minimum, the first
+    /// element is returned.
+    pub fn min(&self) -> Option<&T>
+    where
+        T: Ord,
+    {
+        self.iter().min()
+    }
+
+    /// Return a reference to the maximum element in the vector, or `None` if
+    /// vector is empty. If several elements are equally maximum, the last
+    /// element is returned.
+    pub fn max(&self) -> Option<&T>
+    where
+        T: Ord,
+    {
+        self.iter().max()
+    }
+
+    /// Same as [Self::min], but with respect to the specified comparison
+    /// function.
+    pub fn min_by<F>(&self, mut compare: F) -> Option<&T>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.iter().min_by(|a, b| compare(a, b))
+    }
+
+    /// Same as [Self::max], but with respect to the specified comparison
+    /// function.
+    pub fn max_by<F>(&self, mut compare: F) -> Option<&T>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.iter().max_by(|a, b| compare(a, b))
+    }
+
+    /// Same as [Self::min], but with respect to the key returned by `f`.
+    pub fn min_by_key<B, F>(&self, mut f: F) -> Option<&T>
+    where
+        B: Ord,
+        F: FnMut(&T) -> B,
+    {
+        self.iter().min_by_key(|x| f(x))
+    }
+
+    /// Same as [Self::max], but with respect to the key returned by `f`.
+    pub fn max_by_key<B, F>(&self, mut f: F) -> Option<&T>
+    where
+        B: Ord,
+        F: FnMut(&T) -> B,
+    {
+        self.iter().max_by_key(|x| f(x))
+    }
+
+    /// Return the index of the maximum element with respect to `f`. If
+    /// several elements are equally maximum, index of the last element is
+    /// returned. Return `None` if vector is empty.
+    pub fn position_max_by<F>(&self, mut f: F) -> Option<usize>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let item = self.iter().enumerate().max_by(|a, b| f(a.1, b.1));
+        item.map(|(off, _)| off)
+    }
+
+    /// Return the index of the minimum element with respect to `f`. If
+    /// several elements are equally minimum, index of the first element is
+    /// returned. Return `None` if vector is empty.
+    pub fn position_min_by<F>(&self, mut f: F) -> Option<usize>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let item = self.iter().enumerate().min_by(|a, b| f(a.1, b.1));
+        item.map(|(off, _)| off)
+    }
+
+    /// Return the index of the element that gives the maximum value from
+    /// `f`. If several elements are equally maximum, index of the last
+    /// element is returned. Return `None` if vector is empty.
+    pub fn position_max_by_key<B, F>(&self, mut f: F) -> Option<usize>
+    where
+        B: Ord,
+        F: FnMut(&T) -> B,
+    {
+        let item = self.iter().enumerate().max_by_key(|(_, x)| f(x));
+        item.map(|(off, _)| off)
+    }
+
+    /// Return the index of the element that gives the minimum value from
+    /// `f`. If several elements are equally minimum, index of the first
+    /// element is returned. Return `None` if vector is empty.
+    pub fn position_min_by_key<B, F>(&self, mut f: F) -> Option<usize>
+    where
+        B: Ord,
+        F: FnMut(&T) -> B,
+    {
+        let item = self.iter().enumerate().min_by_key(|(_, x)| f(x));
+        item.map(|(off, _)| off)
+    }
+
+    /// Return the number of elements for which `f` returns true.
+    pub fn count_by<F>(&self, mut f: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.fold_leaves(0, |n, data| n + data.iter().filter(|item| f(item)).count())
+    }
+
+    /// Return the number of elements that are equal to `x`.
+    pub fn count(&self, x: &T) -> usize
+    where
+        T: PartialEq,
+    {
+        self.count_by(|item| item == x)
+    }
+
+    /// Return the index and a reference to the first element for which
+    /// `f` returns true, or `None` if there is no such element. Elements are
+    /// scanned only once, from the beginning of the vector.
+    pub fn find<F>(&self, mut f: F) -> Option<(usize, &T)>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter().enumerate().find(|(_, item)| f(item))
+    }
+
+    /// Binary search this vector, sorted by the key extracted using `f`,
+    /// for `key`. Like [slice::binary_search_by_key], return `Ok` with the
+    /// index of a matching element, or `Err` with the index where `key`
+    /// could be inserted to keep the vector sorted. Elements need not be
+    /// `Ord`, only the extracted key. Search descends the tree once, and at
+    /// every intermediate node on the path compares with the first element
+    /// of its right sub-tree, which takes O(log² n) steps.
+    pub fn binary_search_by_key<B, F>(
+        &self,
+        key: &B,
+        mut f: F,
+    ) -> std::result::Result<usize, usize>
+    where
+        B: Ord,
+        F: FnMut(&T) -> B,
+    {
+        let off = self.root.partition_point(&mut |x| f(x) < *key);
+        match off < self.len && f(self.root.get(off)) == *key {
+            true => Ok(off),
+            false => Err(off),
+        }
+    }
+
+    /// Return the range of indices of all elements whose key, extracted
+    /// using `f`, equals `key`. Vector shall be sorted by the key, and the
+    /// range is located using two searches, like [Self::binary_search_by_key],
+    /// one for the lower bound and another for the upper bound, each taking
+    /// O(log² n) steps. Return an empty range,
+    /// positioned where `key` could be inserted, if there is no match.
+    pub fn equal_range_by_key<B, F>(&self, key: &B, mut f: F) -> ops::Range<usize>
+    where
+        B: Ord,
+        F: FnMut(&T) -> B,
+    {
+        let lo = self.root.partition_point(&mut |x| f(x) < *key);
+        let hi = self.root.partition_point(&mut |x| f(x) <= *key);
+        lo..hi
+    }
+
+    /// Return whether the elements of this vector are sorted, in ascending
+    /// order. An empty vector, or a vector with single element, is sorted.
+    pub fn is_sorted(&self) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.is_sorted_by(|a, b| a <= b)
+    }
+
+    /// Return whether the elements of this vector are sorted using `f`. `f`
+    /// is called for every pair of adjacent elements, including the pairs
+    /// that straddle two leaf nodes, and shall return true if the pair is
+    /// in order.
+    pub fn is_sorted_by<F>(&self, mut f: F) -> bool
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let mut iter = self.iter();
+        let mut prev = match iter.next() {
+            Some(item) => item,
+            None => return true,
         };
+        for item in iter {
+            if !f(prev, item) {
+                return false;
+            }
+            prev = item;
+        }
+        true
+    }
+
+    /// Return whether the elements of this vector are sorted, in ascending
+    /// order, by the key extracted using `f`.
+    pub fn is_sorted_by_key<K, F>(&self, mut f: F) -> bool
+    where
+        K: PartialOrd,
+        F: FnMut(&T) -> K,
+    {
+        self.is_sorted_by(|a, b| f(a) <= f(b))
+    }
+
+    /// Sum all the elements in the vector. An empty vector returns the zero
+    /// value of the type.
+    pub fn sum<S>(&self) -> S
+    where
+        T: Clone,
+        S: Sum<T>,
+    {
+        self.iter().cloned().sum()
+    }
+
+    /// Multiply all the elements in the vector. An empty vector returns the
+    /// one value of the type.
+    pub fn product<P>(&self) -> P
+    where
+        T: Clone,
+        P: Product<T>,
+    {
+        self.iter().cloned().product()
+    }
+
+    /// Reduce the vector by following the shape of the tree. Each leaf node
+    /// is mapped to a value using `leaf`, and values from the left and right
+    /// sub-trees are merged using `combine`. Return `None` if vector is
+    /// empty.
+    ///
+    /// `combine` must be associative, since the grouping of values depends
+    /// on the shape of the tree. For floating point sums this gives pairwise
+    /// summation, which is numerically more accurate than a serial sum.
+    pub fn reduce_balanced<A, F, G>(&self, leaf: F, combine: G) -> Option<A>
+    where
+        F: Fn(&[T]) -> A,
+        G: Fn(A, A) -> A,
+    {
+        self.root.reduce_balanced(&leaf, &combine)
+    }
+
+    /// Same as [Self::reduce_balanced], but only for elements within
+    /// `range`. Only the nodes overlapping `range` are visited, and leaf
+    /// nodes at either end of the range are passed to `leaf` partially.
+    /// Return `None` if range is empty, and `IndexFail` error if range is
+    /// out of bounds.
+    ///
+    /// Nodes don't cache aggregates of their sub-trees, hence `leaf` is
+    /// called for every leaf node overlapping the range, in addition to
+    /// O(log n) intermediate nodes.
+    pub fn range_fold<A, R, F, G>(
+        &self,
+        range: R,
+        leaf: F,
+        combine: G,
+    ) -> Result<Option<A>>
+    where
+        R: RangeBounds<usize>,
+        F: Fn(&[T]) -> A,
+        G: Fn(A, A) -> A,
+    {
+        let (start, end) = to_start_end(range, self.len)?;
+        Ok(self.root.range_fold(start, end, &leaf, &combine))
+    }
+
+    /// Return an iterator over leaf nodes, in order, yielding each leaf node's
+    /// data as a slice along with the offset of its first element within the
+    /// vector. Empty leaf nodes are skipped.
+    pub fn iter_leaves(&self) -> impl Iterator<Item = (usize, &[T])> {
+        let mut stack: Vec<&Node<T>> = vec![self.root.as_ref()];
+        let mut off = 0;
+        std::iter::from_fn(move || loop {
+            match stack.pop()? {
+                Node::M { left, right, .. } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+                Node::Z { data } if data.is_empty() => (),
+                Node::Z { data } => {
+                    let val = (off, data.as_slice());
+                    off += data.len();
+                    break Some(val);
+                }
+            }
+        })
+    }
+
+    /// Return the leaf nodes, in order, each leaf node's data as a slice
+    /// along with the offset of its first element within the vector. Leaf
+    /// nodes are collected in a single walk, so that they can be partitioned
+    /// across multiple consumers without walking the tree again. Refer to
+    /// [Self::iter_leaves] for details.
+    pub fn leaf_ranges(&self) -> Vec<(usize, &[T])> {
+        self.iter_leaves().collect()
+    }
+
+    /// Fold every leaf node's data, as a slice, into an accumulator using
+    /// `f`, in order. Refer to [Self::iter_leaves] for details.
+    pub fn fold_leaves<A, F>(&self, init: A, mut f: F) -> A
+    where
+        F: FnMut(A, &[T]) -> A,
+    {
+        self.iter_leaves().fold(init, |acc, (_, data)| f(acc, data))
+    }
+
+    /// Fallible version of [Self::reduce_balanced]. Return the first error
+    /// returned by `combine`, without visiting the rest of the tree.
+    pub fn try_reduce_tree<A, E, F, G>(
+        &self,
+        leaf: F,
+        combine: G,
+    ) -> std::result::Result<Option<A>, E>
+    where
+        F: Fn(&[T]) -> A,
+        G: Fn(A, A) -> std::result::Result<A, E>,
+    {
+        self.root.try_reduce_tree(&leaf, &combine)
+    }
+
+    /// Merge this vector with `other`, both assumed to be sorted with respect
+    /// to `cmp`, into a new sorted vector. The merge is stable, that is,
+    /// between equal elements the ones from this vector come first.
+    pub fn merge_by<F>(&self, other: &Vector<T>, mut cmp: F) -> Vector<T>
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+        loop {
+            let item = match (a.peek(), b.peek()) {
+                (Some(x), Some(y)) if cmp(x, y) == Ordering::Greater => b.next(),
+                (Some(_), _) => a.next(),
+                (None, _) => b.next(),
+            };
+            match item {
+                Some(item) => lb.push(item.clone()),
+                None => break,
+            }
+        }
+        lb.build(self.auto_rebalance)
+    }
+
+    /// Merge this vector with `other`, both assumed to be sorted, into a new
+    /// sorted vector. Same as [Self::merge_by] with `Ord::cmp`, except that
+    /// a leaf node, from either side, whose elements all fall before the
+    /// next element from the other side is shared with the new vector
+    /// instead of being copied. Elements are copied only where the two
+    /// vectors overlap, which makes it cheap to merge vectors holding mostly
+    /// disjoint ranges.
+    pub fn merge_sorted_shared(&self, other: &Vector<T>) -> Vector<T>
+    where
+        T: Clone + Ord,
+    {
+        // leaf nodes in reverse order, so that the next leaf node is popped.
+        let leafs = |arr: &Vector<T>| {
+            let mut leafs =
+                Node::collect_leaf_nodes(Ref::clone(&arr.root), false, arr.leaf_cap);
+            leafs.retain(|leaf| leaf.len() > 0);
+            leafs.reverse();
+            leafs
+        };
+
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        let (mut a, mut b) = (leafs(self), leafs(other));
+        // offset of the next element within the next leaf node.
+        let (mut ia, mut ib) = (0, 0);
+        loop {
+            match (a.last(), b.last()) {
+                (Some(x), Some(y)) => {
+                    let (x, y) = (x.as_data(), y.as_data());
+                    if ia == 0 && x.last() <= Some(&y[ib]) {
+                        lb.push_leaf(a.pop().unwrap());
+                    } else if ib == 0 && y.last() < Some(&x[ia]) {
+                        lb.push_leaf(b.pop().unwrap());
+                    } else if x[ia] <= y[ib] {
+                        lb.push(x[ia].clone());
+                        ia += 1;
+                        if ia == x.len() {
+                            a.pop();
+                            ia = 0;
+                        }
+                    } else {
+                        lb.push(y[ib].clone());
+                        ib += 1;
+                        if ib == y.len() {
+                            b.pop();
+                            ib = 0;
+                        }
+                    }
+                }
+                (Some(x), None) if ia > 0 => {
+                    x.as_data()[ia..]
+                        .iter()
+                        .for_each(|item| lb.push(item.clone()));
+                    a.pop();
+                    ia = 0;
+                }
+                (Some(_), None) => lb.push_leaf(a.pop().unwrap()),
+                (None, Some(y)) if ib > 0 => {
+                    y.as_data()[ib..]
+                        .iter()
+                        .for_each(|item| lb.push(item.clone()));
+                    b.pop();
+                    ib = 0;
+                }
+                (None, Some(_)) => lb.push_leaf(b.pop().unwrap()),
+                (None, None) => break,
+            }
+        }
+        lb.build(self.auto_rebalance)
+    }
+
+    /// Splits the collection into two at the given index.
+    ///
+    /// Returns a new Vector containing the elements in the range [at, len).
+    /// After the call, the original vector will be left containing the
+    /// elements [0, at) with its previous capacity unchanged.
+    ///
+    /// Optionally, application can call [Self::rebalance] on `self`, and
+    /// the returned vector, to make the vectors fully balanced.
+    pub fn split_off(&mut self, off: usize) -> Result<Vector<T>>
+    where
+        T: Clone,
+    {
+        let val = match off {
+            off if off > self.len => {
+                err_at!(IndexFail, msg: "offset {} out of bounds for length {}", off, self.len)?
+            }
+            off if off == self.len => Vector {
+                len: 0,
+                root: Node::empty_leaf(),
+                auto_rebalance: self.auto_rebalance,
+                leaf_cap: self.leaf_cap,
+                pool: NodePool::default(),
+                on_rebalance: self.on_rebalance.clone(),
+                debug_checks: self.debug_checks,
+            },
+            // move the whole tree, instead of copying the first leaf node.
+            0 => Vector {
+                len: mem::replace(&mut self.len, 0),
+                root: mem::replace(&mut self.root, Node::empty_leaf()),
+                auto_rebalance: self.auto_rebalance,
+                leaf_cap: self.leaf_cap,
+                pool: NodePool::default(),
+                on_rebalance: self.on_rebalance.clone(),
+                debug_checks: self.debug_checks,
+            },
+            off => {
+                let (node, root, n) = self.root.split_off(off, self.len);
+                self.root = node;
+                self.len -= n;
+                Vector {
+                    len: n,
+                    root,
+                    auto_rebalance: self.auto_rebalance,
+                    leaf_cap: self.leaf_cap,
+                    pool: NodePool::default(),
+                    on_rebalance: self.on_rebalance.clone(),
+                    debug_checks: self.debug_checks,
+                }
+            }
+        };
+
+        self.debug_check("split_off");
+        val.debug_check("split_off");
+        Ok(val)
+    }
+
+    /// Same as [Self::split_off], except that the vector is split only when
+    /// both the halves are non-empty, that is `0 < off < len`. Return `None`
+    /// otherwise, leaving this vector unchanged.
+    pub fn split_off_nonempty(&mut self, off: usize) -> Option<Vector<T>>
+    where
+        T: Clone,
+    {
+        match off {
+            off if off > 0 && off < self.len => self.split_off(off).ok(),
+            _ => None,
+        }
+    }
+
+    /// Splits the collection into two at the first element for which `pred`
+    /// returns true.
+    ///
+    /// Returns a new Vector containing the elements starting from the first
+    /// match. If no element matches, returned vector is empty and `self` is
+    /// left unchanged.
+    pub fn split_off_by<F>(&mut self, pred: F) -> Vector<T>
+    where
+        T: Clone,
+        F: FnMut(&T) -> bool,
+    {
+        let off = self.iter().position(pred).unwrap_or(self.len);
+        // off is always within bounds.
+        self.split_off(off).unwrap()
+    }
+
+    /// Drop elements from the front of the vector until its
+    /// [footprint](Self::footprint) fits within `max_bytes`. Elements are
+    /// dropped a leaf node at a time, tracking the bytes released by each
+    /// leaf node without walking the whole tree again, hence the resulting
+    /// footprint is an estimate. Vector is emptied if it can't fit within
+    /// `max_bytes`.
+    pub fn trim_to_footprint(&mut self, max_bytes: usize)
+    where
+        T: Clone,
+    {
+        let mut footprint = self.footprint();
+        let mut stack: Vec<&Node<T>> = vec![self.root.as_ref()];
+        let mut off = 0;
+        while footprint > max_bytes {
+            match stack.pop() {
+                Some(Node::M { left, right, .. }) => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+                Some(node @ Node::Z { data }) => {
+                    // every leaf node, except the last, comes with an
+                    // intermediate node.
+                    let n = mem::size_of_val(node) * 2;
+                    let n = n + data.capacity() * mem::size_of::<T>();
+                    footprint = footprint.saturating_sub(n);
+                    off += data.len();
+                }
+                None => break,
+            }
+        }
+
+        match off {
+            0 => (),
+            // off is always within bounds.
+            off => {
+                let val = self.split_off(off).unwrap();
+                self.replace_tree(val);
+            }
+        }
+    }
+
+    /// Remove the elements within `range` from this vector and return them
+    /// as a new vector, or `IndexFail` error if range is out of bounds.
+    /// Remaining elements are joined back without copying the leaf nodes.
+    ///
+    /// Optionally, application can call [Self::rebalance] on `self`, and
+    /// the returned vector, to make the vectors fully balanced.
+    pub fn remove_range<R>(&mut self, range: R) -> Result<Vector<T>>
+    where
+        T: Clone,
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = to_start_end(range, self.len)?;
+
+        let tail = self.split_off(end)?;
+        let val = self.split_off(start)?;
+        if !tail.is_empty() {
+            self.append(tail);
+        }
+
+        Ok(val)
+    }
+
+    /// Exchange the elements within range `a` with the elements within
+    /// range `b`. Spans are swapped by splitting and joining sub-trees,
+    /// without copying the elements. Return `IndexFail` error if either of
+    /// the range is out of bounds, and `InvalidInput` error if ranges
+    /// overlap or differ in length.
+    ///
+    /// Optionally, application can call [Self::rebalance] to make the vector
+    /// fully balanced.
+    pub fn swap_ranges(
+        &mut self,
+        a: ops::Range<usize>,
+        b: ops::Range<usize>,
+    ) -> Result<()>
+    where
+        T: Clone,
+    {
+        let a = to_start_end(a, self.len)?;
+        let b = to_start_end(b, self.len)?;
+        let (x, y) = if a.0 <= b.0 { (a, b) } else { (b, a) };
+
+        if (x.1 - x.0) != (y.1 - y.0) {
+            err_at!(InvalidInput, msg: "range length mismatch {:?} {:?}", x, y)?
+        } else if x.1 > y.0 {
+            err_at!(InvalidInput, msg: "range overlap {:?} {:?}", x, y)?
+        } else if x.0 == x.1 {
+            return Ok(());
+        }
+
+        let tail = self.split_off(y.1)?;
+        let ys = self.split_off(y.0)?;
+        let mid = self.split_off(x.1)?;
+        let xs = self.split_off(x.0)?;
+        for arr in [ys, mid, xs, tail] {
+            if !arr.is_empty() {
+                self.append(arr)
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return an iterator over sub-vectors, sharing the leaf nodes of this
+    /// vector, separated by elements equal to `delim`. Like [str::split],
+    /// consecutive delimiters, and delimiters at either end, yield empty
+    /// sub-vectors, and an empty vector yields a single empty sub-vector.
+    pub fn split_on<'a>(&'a self, delim: &'a T) -> impl Iterator<Item = Vector<T>> + 'a
+    where
+        T: Clone + PartialEq,
+    {
+        let mut iter = self.iter().enumerate();
+        let mut start = Some(0);
+        std::iter::from_fn(move || {
+            let from = start?;
+            match iter.find(|(_, item)| *item == delim) {
+                Some((off, _)) => {
+                    start = Some(off + 1);
+                    Some(self.slice(from, off))
+                }
+                None => {
+                    start = None;
+                    Some(self.slice(from, self.len))
+                }
+            }
+        })
+    }
+
+    /// Return a deep copy of the elements within `range` as a new vector,
+    /// or `IndexFail` error if range is out of bounds.
+    ///
+    /// Unlike sub-vectors returned by apis like [Self::split_off], that share
+    /// leaf nodes with this vector, elements are cloned into new leaf nodes.
+    /// Returned vector is under single ownership and can be mutated in-place
+    /// using the `_mut` apis, at the cost of copying the elements upfront.
+    pub fn clone_range<R>(&self, range: R) -> Result<Vector<T>>
+    where
+        T: Clone,
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = to_start_end(range, self.len)?;
+
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        for (off, data) in self.iter_leaves() {
+            if off >= end {
+                break;
+            } else if off + data.len() <= start {
+                continue;
+            }
+            let from = start.saturating_sub(off);
+            let till = (end - off).min(data.len());
+            data[from..till]
+                .iter()
+                .for_each(|item| lb.push(item.clone()));
+        }
+
+        Ok(lb.build(self.auto_rebalance))
+    }
+
+    /// Return an iterator over `size` elements of the vector at a time,
+    /// starting at the end of the vector, as sub-vectors sharing the leaf
+    /// nodes of this vector. If vector's length is not a multiple of `size`
+    /// the last chunk, holding the front of the vector, shall be shorter.
+    /// Return `InvalidInput` error if `size` is ZERO.
+    pub fn rchunks(&self, size: usize) -> Result<impl Iterator<Item = Vector<T>> + '_>
+    where
+        T: Clone,
+    {
+        if size == 0 {
+            err_at!(InvalidInput, msg: "chunk size is ZERO")?
+        }
+
+        let mut end = self.len;
+        let iter = std::iter::from_fn(move || match end {
+            0 => None,
+            _ => {
+                let start = end.saturating_sub(size);
+                let val = self.slice(start, end);
+                end = start;
+                Some(val)
+            }
+        });
+        Ok(iter)
+    }
+
+    /// Return an iterator over all contiguous windows of `size` elements,
+    /// overlapping, starting at the beginning of the vector. Windows that
+    /// fall within a single leaf node are borrowed as slices, without
+    /// copying, and only windows that straddle leaf nodes are returned as
+    /// sub-vectors, refer to [WindowRef]. If vector's length is less than
+    /// `size`, iterator returns no windows. Return `InvalidInput` error if
+    /// `size` is ZERO.
+    pub fn windows_ref(&self, size: usize) -> Result<WindowsRef<'_, T>>
+    where
+        T: Clone,
+    {
+        if size == 0 {
+            err_at!(InvalidInput, msg: "window size is ZERO")?
+        }
+
+        let val = WindowsRef {
+            arr: self,
+            off: 0,
+            size,
+            leaf: &[],
+            leaf_off: 0,
+        };
+        Ok(val)
+    }
+
+    /// Return an iterator over `size` elements of the vector at a time,
+    /// starting at the beginning of the vector, as sub-vectors sharing the
+    /// leaf nodes of this vector. If vector's length is not a multiple of
+    /// `size` the last up to `size-1` elements are omitted, and can be
+    /// retrieved using [ChunksExact::remainder]. Return `InvalidInput`
+    /// error if `size` is ZERO.
+    pub fn chunks_exact(&self, size: usize) -> Result<ChunksExact<'_, T>>
+    where
+        T: Clone,
+    {
+        if size == 0 {
+            err_at!(InvalidInput, msg: "chunk size is ZERO")?
+        }
+
+        let val = ChunksExact {
+            arr: self,
+            off: 0,
+            end: self.len - (self.len % size),
+            size,
+        };
+        Ok(val)
+    }
+
+    /// Split the vector into batches of `k` elements, as sub-vectors sharing
+    /// the leaf nodes of this vector. If vector's length is not a multiple
+    /// of `k` the last batch shall be shorter. Unlike [Self::chunks_exact],
+    /// batches are returned as owned vectors, which can be handed over to
+    /// other threads. Return `InvalidInput` error if `k` is ZERO.
+    pub fn split_every(&self, k: usize) -> Result<Vec<Vector<T>>>
+    where
+        T: Clone,
+    {
+        if k == 0 {
+            err_at!(InvalidInput, msg: "batch size is ZERO")?
+        }
+
+        let batches = (0..self.len)
+            .step_by(k)
+            .map(|start| self.slice(start, (start + k).min(self.len)))
+            .collect();
+        Ok(batches)
+    }
+
+    /// Divide the vector into two halves, as sub-vectors sharing the leaf
+    /// nodes of this vector. Vector is divided at the leaf node boundary
+    /// closest to `len/2`, hence unlike [Self::split_at_checked] no leaf
+    /// node is copied and halves are only roughly equal in length. Useful
+    /// for divide-and-conquer, like seeding a `rayon::join`.
+    pub fn halve(&self) -> (Vector<T>, Vector<T>)
+    where
+        T: Clone,
+    {
+        let mid = self.len / 2;
+        let off = match self.len {
+            0 => 0,
+            _ => {
+                let (data, off) = self.root.get_leaf(mid);
+                let (start, end) = (mid - off, mid - off + data.len());
+                match (mid - start) <= (end - mid) {
+                    true => start,
+                    false => end,
+                }
+            }
+        };
+        (self.slice(0, off), self.slice(off, self.len))
+    }
+
+    /// Divide the vector into two at `mid`, as sub-vectors sharing the leaf
+    /// nodes of this vector. First vector shall contain elements within
+    /// [0, mid), and the second vector shall contain elements within
+    /// [mid, len). Return `None` if `mid > len`.
+    pub fn split_at_checked(&self, mid: usize) -> Option<(Vector<T>, Vector<T>)>
+    where
+        T: Clone,
+    {
+        let mut left = self.clone();
+        let right = left.split_off(mid).ok()?;
+        Some((left, right))
+    }
+
+    /// Return a sub-vector, sharing the leaf nodes of this vector, after
+    /// removing all leading elements for which `f` returns true.
+    pub fn trim_start_matches<F>(&self, mut f: F) -> Vector<T>
+    where
+        T: Clone,
+        F: FnMut(&T) -> bool,
+    {
+        let start = self.iter().take_while(|item| f(item)).count();
+        self.slice(start, self.len)
+    }
+
+    /// Return a sub-vector, sharing the leaf nodes of this vector, after
+    /// removing all trailing elements for which `f` returns true.
+    pub fn trim_end_matches<F>(&self, mut f: F) -> Vector<T>
+    where
+        T: Clone,
+        F: FnMut(&T) -> bool,
+    {
+        let n = self.rev_iter().take_while(|item| f(item)).count();
+        self.slice(0, self.len - n)
+    }
+
+    fn debug_check(&self, op: &str) {
+        if self.debug_checks {
+            if let Err(err) = self.verify() {
+                panic!("{} after {}", err, op)
+            }
+        }
+    }
+
+    // replace the tree of this vector with the tree of `other`, retaining
+    // this vector's settings.
+    fn replace_tree(&mut self, other: Vector<T>) {
+        self.len = other.len;
+        self.root = other.root;
+    }
+
+    // return a new vector sharing the leaf nodes of this vector, for items
+    // within [start, end).
+    fn slice(&self, start: usize, end: usize) -> Vector<T>
+    where
+        T: Clone,
+    {
+        debug_assert!(start <= end && end <= self.len);
+
+        if start == 0 && end == self.len {
+            return self.clone();
+        }
+
+        let mut val = self.clone();
+        if end < val.len {
+            val.split_off(end).unwrap();
+        }
+        val.split_off(start).unwrap()
+    }
+
+    /// Join `other` Vector into this vector. If auto-rebalance is enabled
+    /// and the joined tree is too skewed, the tree shall be rebalanced.
+    ///
+    /// Call [Self::rebalance] on `self` to make the vectors fully balanced.
+    pub fn append(&mut self, other: Vector<T>)
+    where
+        T: Clone,
+    {
+        self.append_no_rebalance(other);
+
+        let rn = Rebalance::new(self, &self.on_rebalance);
+        let depth = self.root.spine_depth();
+        let packed = false;
+        let force = false;
+        let (root, _) =
+            Node::auto_rebalance(Ref::clone(&self.root), depth, packed, force, &rn);
+        self.root = root;
+        debug_assert_eq!(self.len, self.root.len());
+    }
+
+    /// Same as [Self::append], except that the joined tree is never
+    /// rebalanced.
+    ///
+    /// Call [Self::rebalance] on `self` to make the vectors fully balanced.
+    pub fn append_no_rebalance(&mut self, other: Vector<T>)
+    where
+        T: Clone,
+    {
+        let other = if other.leaf_cap != self.leaf_cap {
+            let arr: Vec<T> = other.into();
+            Vector::from_slice(&arr, Some(self.leaf_cap))
+        } else {
+            other
+        };
+
+        let root = {
+            let left = Ref::clone(&self.root);
+            let right = Ref::clone(&other.root);
+            Node::newm(left, right, self.len)
+        };
+        self.root = root;
+        // appending a vector to itself doubles the length without using
+        // more memory, which can overflow on 32-bit targets.
+        self.len = match self.len.checked_add(other.len) {
+            Some(len) => len,
+            None => panic!("vector length overflow {} + {}", self.len, other.len),
+        };
+        debug_assert_eq!(self.len, self.root.len());
+        self.debug_check("append");
+    }
+
+    /// Same as [Self::append], except that `other` can be anything that
+    /// converts into a vector, like a `Vec<T>` or a `&[T]`. Converted
+    /// vectors use the default leaf node size, and like [Self::append] they
+    /// are rebuilt if this vector uses a different leaf node size.
+    pub fn append_any<V>(&mut self, other: V)
+    where
+        T: Clone,
+        V: Into<Vector<T>>,
+    {
+        self.append(other.into())
+    }
+
+    /// Concatenate all vectors from `parts` into a single balanced vector,
+    /// in the same order. Leaf nodes are shared with `parts`, and they are
+    /// collected as each part arrives, so that `parts` can be generated
+    /// lazily. The tree is built once, after the last part.
+    ///
+    /// Settings, like leaf node size, are taken from the first part. Like
+    /// [Self::append], parts using a different leaf node size are rebuilt
+    /// with the first part's leaf node size.
+    pub fn concat_iter<I>(parts: I) -> Vector<T>
+    where
+        T: Clone,
+        I: IntoIterator<Item = Vector<T>>,
+    {
+        let mut parts = parts.into_iter();
+        let mut val = match parts.next() {
+            Some(val) => val,
+            None => return Vector::new(),
+        };
+
+        let mut leafs = vec![];
+        let mut len = 0;
+        for part in std::iter::once(val.clone()).chain(parts) {
+            len += part.len;
+            let part = if part.leaf_cap != val.leaf_cap {
+                let arr: Vec<T> = part.into();
+                Vector::from_slice(&arr, Some(val.leaf_cap))
+            } else {
+                part
+            };
+            let iter = Node::collect_leaf_nodes(part.root, false, part.leaf_cap)
+                .into_iter()
+                .filter(|leaf| leaf.len() > 0);
+            leafs.extend(iter);
+        }
+        leafs.reverse();
+
+        let depth = (leafs.len() as f64).log2().ceil() as usize;
+        let (root, n) = Node::build_bottoms_up(depth, &mut leafs);
+        debug_assert!(leafs.is_empty() && n == len);
+
+        val.len = len;
+        val.root = root;
+        val
+    }
+
+    /// When auto-rebalance is disabled, use this method to rebalance the tree.
+    /// Calling it with `packed` as true will make sure that the leaf nodes
+    /// are fully packed when rebuilding the tree.
+    ///
+    /// If the tree is already as balanced as a rebuilt tree, and its leaf
+    /// nodes are already packed when `packed` is true, tree is not rebuilt
+    /// and a clone of this vector is returned. Use [Self::force_rebalance]
+    /// to always rebuild the tree.
+    pub fn rebalance(&self, packed: bool) -> Result<Self>
+    where
+        T: Clone,
+    {
+        let (depth, n_leafs) = self.root.depth_leafs();
+        let ideal = (n_leafs as f64).log2().ceil() as usize + 1;
+        let cap = max_leaf_items::<T>(self.leaf_cap);
+
+        if depth <= ideal && (!packed || self.root.is_packed(cap)) {
+            Ok(self.clone())
+        } else {
+            self.force_rebalance(packed)
+        }
+    }
+
+    /// Same as [Self::rebalance], except that the tree is always rebuilt.
+    pub fn force_rebalance(&self, packed: bool) -> Result<Self>
+    where
+        T: Clone,
+    {
+        let rn = Rebalance::new(self, &self.on_rebalance);
+        let root = Ref::clone(&self.root);
+        let (root, _depth) = Node::auto_rebalance(root, 0, packed, true, &rn);
+        let val = Vector {
+            len: self.len,
+            root,
+            auto_rebalance: self.auto_rebalance,
+            leaf_cap: self.leaf_cap,
+            pool: NodePool::default(),
+            on_rebalance: self.on_rebalance.clone(),
+            debug_checks: self.debug_checks,
+        };
+        Ok(val)
+    }
+
+    /// Consume this vector, apply `f` on every element and rebuild them
+    /// into a balanced tree with packed leaf nodes. Same as a map followed
+    /// by a `force_rebalance(true)`, but in single pass.
+    ///
+    /// Elements are moved out of leaf nodes that are not shared with other
+    /// vectors, and cloned otherwise. If `leaf_cap` is None, this vector's
+    /// leaf node size is used.
+    pub fn rebuild_with<F>(mut self, mut f: F, leaf_cap: Option<usize>) -> Vector<T>
+    where
+        T: Clone,
+        F: FnMut(T) -> T,
+    {
+        let leaf_cap = leaf_cap.unwrap_or(self.leaf_cap);
+
+        let mut iter = IntoIter {
+            stack: Vec::default(),
+            leaf: None,
+        };
+        let root = mem::replace(&mut self.root, Node::empty_leaf());
+        Node::build_into_iter_stack(root, &mut iter);
+
+        let mut lb = LeafBuilder::new(leaf_cap);
+        iter.for_each(|item| lb.push(f(item)));
+
+        self.leaf_cap = leaf_cap;
+        self.replace_tree(lb.build(self.auto_rebalance));
+        self
+    }
+
+    /// Split the vector into two, the first containing all the elements
+    /// for which `f` returns true and the second containing all elements
+    /// for which `f` returns false. Order of elements within each output
+    /// vector is same as their order in this vector.
+    pub fn partition<F>(&self, mut f: F) -> (Vector<T>, Vector<T>)
+    where
+        T: Clone,
+        F: FnMut(&T) -> bool,
+    {
+        let mut left = LeafBuilder::new(self.leaf_cap);
+        let mut right = LeafBuilder::new(self.leaf_cap);
+
+        for item in self.iter() {
+            match f(item) {
+                true => left.push(item.clone()),
+                false => right.push(item.clone()),
+            }
+        }
+
+        let left = left.build(self.auto_rebalance);
+        let right = right.build(self.auto_rebalance);
+        (left, right)
+    }
+
+    /// Return a new vector with only the first occurrence of every element,
+    /// in the same order. Unlike [Self::dedup_by_key], duplicates need not
+    /// be consecutive.
+    pub fn unique(&self) -> Vector<T>
+    where
+        T: Clone + std::hash::Hash + Eq,
+    {
+        let mut seen = std::collections::HashSet::new();
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        for (_, data) in self.iter_leaves() {
+            for item in data.iter() {
+                if seen.insert(item) {
+                    lb.push(item.clone())
+                }
+            }
+        }
+        lb.build(self.auto_rebalance)
+    }
+
+    /// Remove all the elements for which `f` returns true and return them
+    /// as a new vector, leaving behind the elements for which `f` returns
+    /// false. Order of elements is preserved in both vectors. Refer to
+    /// [Self::partition] for details.
+    pub fn extract_if<F>(&mut self, f: F) -> Vector<T>
+    where
+        T: Clone,
+        F: FnMut(&T) -> bool,
+    {
+        let (extracted, retained) = self.partition(f);
+        self.replace_tree(retained);
+        extracted
+    }
 
-        Ok(val)
+    /// Combine this vector with `other`, element by element, using `f`.
+    /// Like [Iterator::zip], if the two vectors are of different length,
+    /// the returned vector is truncated to the shorter one. Use
+    /// [Self::try_zip_with] to treat length mismatch as error.
+    pub fn zip_with<U, V, F>(&self, other: &Vector<U>, mut f: F) -> Vector<V>
+    where
+        F: FnMut(&T, &U) -> V,
+    {
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        for (x, y) in self.iter().zip(other.iter()) {
+            lb.push(f(x, y))
+        }
+        lb.build(self.auto_rebalance)
     }
 
-    /// Join `other` Vector into this vector.
-    ///
-    /// Call [Self::rebalance] on `self` to make the vectors fully balanced.
-    pub fn append(&mut self, other: Vector<T>)
+    /// Same as [Self::zip_with], but return `InvalidInput` error if this
+    /// vector and `other` are of different length.
+    pub fn try_zip_with<U, V, F>(&self, other: &Vector<U>, f: F) -> Result<Vector<V>>
     where
-        T: Clone,
+        F: FnMut(&T, &U) -> V,
     {
-        let other = if other.leaf_cap != self.leaf_cap {
-            let arr: Vec<T> = other.into();
-            Vector::from_slice(&arr, Some(self.leaf_cap))
+        if self.len == other.len {
+            Ok(self.zip_with(other, f))
         } else {
-            other
-        };
+            err_at!(InvalidInput, msg: "length mismatch {} != {}", self.len, other.len)
+        }
+    }
 
-        let root = {
-            let left = Ref::clone(&self.root);
-            let right = Ref::clone(&other.root);
-            Node::newm(left, right, self.len)
-        };
-        self.root = root;
-        self.len += other.len;
+    /// Thread an accumulator through the elements of this vector, in order,
+    /// and return a new vector of each intermediate accumulator state.
+    /// For example, a prefix-sum can be computed as
+    /// `arr.scan(0, |acc, x| acc + x)`.
+    pub fn scan<A, F>(&self, init: A, mut f: F) -> Vector<A>
+    where
+        A: Clone,
+        F: FnMut(&A, &T) -> A,
+    {
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        let mut acc = init;
+        for item in self.iter() {
+            acc = f(&acc, item);
+            lb.push(acc.clone());
+        }
+        lb.build(self.auto_rebalance)
     }
 
-    /// When auto-rebalance is disabled, use this method to rebalance the tree.
-    /// Calling it with `packed` as true will make sure that the leaf nodes
-    /// are fully packed when rebuilding the tree.
-    pub fn rebalance(&self, packed: bool) -> Result<Self>
+    /// Map every leaf node's data using `f`, and return a new vector made of
+    /// the mapped leaf nodes, in the same order. Mapped leaf nodes are
+    /// remembered in `cache`, by the address of the source leaf node, so
+    /// that leaf nodes shared across versions of a vector are mapped only
+    /// once. `f` shall be deterministic for the cache to be meaningful.
+    ///
+    /// `f` may return any number of elements for a leaf node, and the
+    /// mapped leaf nodes are used as is.
+    pub fn map_cached<U, F>(&self, mut f: F, cache: &mut MapCache<T, U>) -> Vector<U>
+    where
+        F: FnMut(&[T]) -> Vec<U>,
+    {
+        let mut lb = LeafBuilder::new(self.leaf_cap);
+        let mut stack: Vec<&Ref<Node<T>>> = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            match node.as_ref() {
+                Node::M { left, right, .. } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+                Node::Z { data } if data.is_empty() => (),
+                Node::Z { data } => {
+                    let key = Ref::as_ptr(node) as *const u8 as usize;
+                    let leaf = match cache.leafs.get(&key) {
+                        Some((weak, leaf)) if is_same_node(weak, node) => {
+                            Ref::clone(leaf)
+                        }
+                        // missing or stale entry.
+                        Some(_) | None => {
+                            let leaf = Ref::new(Node::Z { data: f(data) });
+                            let val = (Ref::downgrade(node), Ref::clone(&leaf));
+                            cache.leafs.insert(key, val);
+                            leaf
+                        }
+                    };
+                    if leaf.len() > 0 {
+                        lb.push_leaf(leaf)
+                    }
+                }
+            }
+        }
+        lb.build(self.auto_rebalance)
+    }
+
+    /// Rebuild the tree with fully packed leaf nodes of `leaf_size` bytes, and
+    /// return the new vector configured with the new leaf size. Unlike
+    /// [Self::rebalance], which uses the current leaf size, this can be used
+    /// to switch a vector between large leaf nodes, for bulk loading and fast
+    /// reads, and small leaf nodes, for cheaper copy-on-write inserts.
+    pub fn repack(&self, leaf_size: usize) -> Vector<T>
     where
         T: Clone,
     {
-        let rn = Rebalance::new(self);
-        let root = Ref::clone(&self.root);
-        let (root, _depth) = Node::auto_rebalance(root, 0, packed, true, &rn);
-        let val = Vector {
-            len: self.len,
-            root,
-            auto_rebalance: self.auto_rebalance,
-            leaf_cap: self.leaf_cap,
-        };
-        Ok(val)
+        let mut lb = LeafBuilder::new(leaf_size);
+        self.iter().for_each(|item| lb.push(item.clone()));
+        lb.build(self.auto_rebalance)
+    }
+
+    /// Return the structure of the tree as a multi-line string, for logging
+    /// and bug reports. First line describes the vector, followed by one
+    /// line for every node, in depth-first order and indented by its depth.
+    /// Intermediate nodes are described as `nodem:<len> weight:<weight>`
+    /// and leaf nodes as `nodez:<len>`. Elements are not included.
+    pub fn describe_tree(&self) -> String {
+        let (depth, n_leafs) = self.root.depth_leafs();
+        let mut out = format!(
+            "vector len:{} leaf_cap:{} depth:{} leafs:{}\n",
+            self.len, self.leaf_cap, depth, n_leafs
+        );
+        self.root.describe_tree("", self.len, &mut out);
+        out
+    }
+
+    /// Verify the integrity of the tree, that is, weight of every
+    /// intermediate node matches the number of items in its left sub-tree
+    /// and the total number of items in the tree matches the vector's
+    /// length. Return `Fatal` error on mismatch.
+    pub fn verify(&self) -> Result<()> {
+        match self.root.verify()? {
+            n if n == self.len => Ok(()),
+            n => err_at!(Fatal, msg: "length mismatch {} != {}", n, self.len),
+        }
     }
 
     // return only nodes that is referenced in multiple-versions. and
@@ -411,7 +2547,7 @@ where
     #[cfg(test)]
     #[allow(dead_code)]
     fn pretty_print(&self) {
-        self.root.pretty_print("".to_string(), self.len)
+        print!("{}", self.describe_tree())
     }
 }
 
@@ -464,6 +2600,84 @@ where
         }
     }
 
+    // return (depth, number-of-leaf-nodes) for this sub-tree.
+    fn depth_leafs(&self) -> (usize, usize) {
+        match self {
+            Node::M { left, right, .. } => {
+                let (ldepth, lleafs) = left.depth_leafs();
+                let (rdepth, rleafs) = right.depth_leafs();
+                (ldepth.max(rdepth) + 1, lleafs + rleafs)
+            }
+            Node::Z { .. } => (1, 1),
+        }
+    }
+
+    // return whether leaf nodes are packed with `cap` items, except the last
+    // leaf node which shall be non-empty, unless it is the only leaf node.
+    fn is_packed(&self, cap: usize) -> bool {
+        let mut stack = vec![self];
+        let mut last: Option<usize> = None;
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::M { left, right, .. } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+                Node::Z { data } => match last {
+                    Some(n) if n != cap => return false,
+                    _ => last = Some(data.len()),
+                },
+            }
+        }
+        !matches!((last, self), (Some(0), Node::M { .. }))
+    }
+
+    // return the first element, descending only once, using the weight to
+    // skip an empty left sub-tree.
+    fn first(&self) -> Option<&T> {
+        match self {
+            Node::M {
+                weight: 0, right, ..
+            } => right.first(),
+            Node::M { left, .. } => left.first(),
+            Node::Z { data } => data.first(),
+        }
+    }
+
+    // return the number of leading elements for which `pred` is true,
+    // elements shall be partitioned by `pred`. Every intermediate node on the
+    // path looks up the first element of its right sub-tree, hence the cost
+    // is O(depth^2).
+    fn partition_point(&self, pred: &mut dyn FnMut(&T) -> bool) -> usize {
+        match self {
+            Node::M {
+                weight,
+                left,
+                right,
+            } => match right.first() {
+                Some(item) if pred(item) => weight + right.partition_point(pred),
+                _ => left.partition_point(pred),
+            },
+            Node::Z { data } => data.partition_point(|item| pred(item)),
+        }
+    }
+
+    // return the depth of the longer of left-most and right-most paths,
+    // which is where repeated append and prepend skew the tree.
+    fn spine_depth(&self) -> usize {
+        let (mut ldepth, mut node) = (1, self);
+        while let Node::M { left, .. } = node {
+            ldepth += 1;
+            node = left;
+        }
+        let (mut rdepth, mut node) = (1, self);
+        while let Node::M { right, .. } = node {
+            rdepth += 1;
+            node = right;
+        }
+        ldepth.max(rdepth)
+    }
+
     fn cow(&self) -> Node<T>
     where
         T: Clone,
@@ -476,6 +2690,20 @@ where
         }
     }
 
+    fn as_data(&self) -> &[T] {
+        match self {
+            Node::Z { data } => data,
+            _ => unreachable!(),
+        }
+    }
+
+    fn into_data(self) -> Vec<T> {
+        match self {
+            Node::Z { data } => data,
+            _ => unreachable!(),
+        }
+    }
+
     fn pack(&mut self, other: &Self, cap: usize) -> Option<Self>
     where
         T: Clone,
@@ -503,6 +2731,13 @@ where
         }
     }
 
+    fn capacity(&self) -> usize {
+        match self {
+            Node::M { left, right, .. } => left.capacity() + right.capacity(),
+            Node::Z { data } => data.capacity(),
+        }
+    }
+
     fn footprint(&self) -> usize {
         let n = mem::size_of_val(self);
         n + match self {
@@ -511,6 +2746,23 @@ where
         }
     }
 
+    fn memory_report(&self, depth: usize, report: &mut MemoryReport) {
+        report.depth = report.depth.max(depth);
+        match self {
+            Node::M { left, right, .. } => {
+                report.spine_bytes += mem::size_of_val(self);
+                left.memory_report(depth + 1, report);
+                right.memory_report(depth + 1, report);
+            }
+            Node::Z { data } => {
+                report.n_leafs += 1;
+                report.leaf_bytes += mem::size_of_val(self);
+                report.data_bytes += data.len() * mem::size_of::<T>();
+                report.data_capacity_bytes += data.capacity() * mem::size_of::<T>();
+            }
+        }
+    }
+
     fn get(&self, off: usize) -> &T {
         match self {
             Node::M { weight, left, .. } if off < *weight => left.get(off),
@@ -519,8 +2771,73 @@ where
         }
     }
 
+    // return the leaf node's data containing `off`, along with the offset
+    // of the element within that leaf node.
+    fn get_leaf(&self, off: usize) -> (&[T], usize) {
+        match self {
+            Node::M { weight, left, .. } if off < *weight => left.get_leaf(off),
+            Node::M { weight, right, .. } => right.get_leaf(off - *weight),
+            Node::Z { data } => (data.as_slice(), off),
+        }
+    }
+
+    fn get_mut(&mut self, off: usize) -> Result<&mut T> {
+        match self {
+            Node::M { weight, left, .. } if off < *weight => match Ref::get_mut(left) {
+                Some(left) => left.get_mut(off),
+                None => err_at!(Shared, msg: "vector is shared"),
+            },
+            Node::M { weight, right, .. } => match Ref::get_mut(right) {
+                Some(right) => right.get_mut(off - *weight),
+                None => err_at!(Shared, msg: "vector is shared"),
+            },
+            Node::Z { data } => Ok(&mut data[off]),
+        }
+    }
+
+    // `i` shall be less than `j`
+    fn get_disjoint_mut(&mut self, i: usize, j: usize) -> Result<(&mut T, &mut T)> {
+        match self {
+            Node::M {
+                weight,
+                left,
+                right,
+            } => {
+                let weight = *weight;
+                if j < weight {
+                    match Ref::get_mut(left) {
+                        Some(left) => left.get_disjoint_mut(i, j),
+                        None => err_at!(Shared, msg: "vector is shared"),
+                    }
+                } else if i >= weight {
+                    match Ref::get_mut(right) {
+                        Some(right) => right.get_disjoint_mut(i - weight, j - weight),
+                        None => err_at!(Shared, msg: "vector is shared"),
+                    }
+                } else {
+                    match (Ref::get_mut(left), Ref::get_mut(right)) {
+                        (Some(left), Some(right)) => {
+                            Ok((left.get_mut(i)?, right.get_mut(j - weight)?))
+                        }
+                        _ => err_at!(Shared, msg: "vector is shared"),
+                    }
+                }
+            }
+            Node::Z { data } => {
+                let (a, b) = data.split_at_mut(j);
+                Ok((&mut a[i], &mut b[0]))
+            }
+        }
+    }
+
     // return (value, max_depth)
-    fn insert(&self, off: usize, val: T, rn: &Rebalance) -> Result<(Ref<Node<T>>, usize)>
+    fn insert(
+        &self,
+        off: usize,
+        val: T,
+        rn: &Rebalance,
+        pool: &mut NodePool<T>,
+    ) -> Result<(Ref<Node<T>>, usize)>
     where
         T: Clone,
     {
@@ -532,22 +2849,22 @@ where
             } => {
                 let weight = *weight;
                 let (weight, left, right, depth) = if off < weight {
-                    let (left, depth) = left.insert(off, val, rn)?;
+                    let (left, depth) = left.insert(off, val, rn, pool)?;
                     (weight + 1, left, Ref::clone(right), depth)
                 } else {
                     let off = off - weight;
-                    let (right, depth) = right.insert(off, val, rn)?;
+                    let (right, depth) = right.insert(off, val, rn, pool)?;
                     (weight, Ref::clone(left), right, depth)
                 };
-                (Node::newm(left, right, weight), depth + 1)
+                (pool.newm(left, right, weight), depth + 1)
             }
             Node::Z { data } if data.len() < max_leaf_items::<T>(rn.leaf_cap) => {
                 let mut ndata = data[..off].to_vec();
                 ndata.push(val);
                 ndata.extend_from_slice(&data[off..]);
-                (Ref::new(Node::Z { data: ndata }), 1)
+                (pool.alloc(Node::Z { data: ndata }), 1)
             }
-            Node::Z { data } => (Self::split_insert(data, off, val), 2),
+            Node::Z { data } => (Self::split_insert(data, off, val, pool), 2),
         };
 
         let (node, depth) = Node::auto_rebalance(node, depth, false, false, rn);
@@ -555,7 +2872,13 @@ where
         Ok((node, depth))
     }
 
-    fn insert_mut(&mut self, off: usize, val: T, rn: &Rebalance) -> Result<usize>
+    fn insert_mut(
+        &mut self,
+        off: usize,
+        val: T,
+        rn: &Rebalance,
+        pool: &mut NodePool<T>,
+    ) -> Result<usize>
     where
         T: Clone,
     {
@@ -566,12 +2889,16 @@ where
                 right,
             } => {
                 if off < *weight {
-                    let depth = Ref::get_mut(left).unwrap().insert_mut(off, val, rn)?;
+                    let depth =
+                        Ref::get_mut(left).unwrap().insert_mut(off, val, rn, pool)?;
                     *weight += 1;
-                    depth
+                    depth + 1
                 } else {
                     let off = off - *weight;
-                    Ref::get_mut(right).unwrap().insert_mut(off, val, rn)?
+                    let depth = Ref::get_mut(right)
+                        .unwrap()
+                        .insert_mut(off, val, rn, pool)?;
+                    depth + 1
                 }
             }
             Node::Z { data } if data.len() < max_leaf_items::<T>(rn.leaf_cap) => {
@@ -579,7 +2906,7 @@ where
                 1
             }
             Node::Z { data } => {
-                *self = Ref::try_unwrap(Self::split_insert(data, off, val))
+                *self = Ref::try_unwrap(Self::split_insert(data, off, val, pool))
                     .ok()
                     .unwrap();
                 2
@@ -588,6 +2915,54 @@ where
         Ok(depth)
     }
 
+    // positions in `updates` are relative to the vector, `base` is the
+    // position of this sub-tree's first element.
+    fn update_many(
+        node: &mut Ref<Node<T>>,
+        updates: &[(usize, T)],
+        base: usize,
+        olds: &mut Vec<T>,
+    ) where
+        T: Clone,
+    {
+        if updates.is_empty() {
+            return;
+        }
+
+        if Ref::get_mut(node).is_none() {
+            let copy = match node.as_ref() {
+                Node::M {
+                    weight,
+                    left,
+                    right,
+                } => Node::M {
+                    weight: *weight,
+                    left: Ref::clone(left),
+                    right: Ref::clone(right),
+                },
+                node => node.cow(),
+            };
+            *node = Ref::new(copy);
+        }
+
+        match Ref::get_mut(node).unwrap() {
+            Node::M {
+                weight,
+                left,
+                right,
+            } => {
+                let n = updates.partition_point(|(off, _)| (off - base) < *weight);
+                Self::update_many(left, &updates[..n], base, olds);
+                Self::update_many(right, &updates[n..], base + *weight, olds);
+            }
+            Node::Z { data } => {
+                for (off, value) in updates.iter() {
+                    olds.push(mem::replace(&mut data[off - base], value.clone()));
+                }
+            }
+        }
+    }
+
     fn update(&self, off: usize, value: T) -> (Ref<Node<T>>, T)
     where
         T: Clone,
@@ -638,7 +3013,7 @@ where
         }
     }
 
-    fn remove(&self, off: usize) -> (Ref<Node<T>>, T)
+    fn remove(&self, off: usize, pool: &mut NodePool<T>) -> (Ref<Node<T>>, T)
     where
         T: Clone,
     {
@@ -650,11 +3025,11 @@ where
             } => {
                 let weight = *weight;
                 if off < weight {
-                    let (left, old) = left.remove(off);
-                    (Node::newm(left, Ref::clone(right), weight - 1), old)
+                    let (left, old) = left.remove(off, pool);
+                    (pool.newm(left, Ref::clone(right), weight - 1), old)
                 } else {
-                    let (right, old) = right.remove(off - weight);
-                    (Node::newm(Ref::clone(left), right, weight), old)
+                    let (right, old) = right.remove(off - weight, pool);
+                    (pool.newm(Ref::clone(left), right, weight), old)
                 }
             }
             Node::Z { data } => {
@@ -662,7 +3037,7 @@ where
 
                 let mut ndata = data[..off].to_vec();
                 ndata.extend_from_slice(&data[(off + 1)..]);
-                (Ref::new(Node::Z { data: ndata }), old)
+                (pool.alloc(Node::Z { data: ndata }), old)
             }
         }
     }
@@ -695,7 +3070,12 @@ where
         }
     }
 
-    fn split_insert(data: &[T], off: usize, val: T) -> Ref<Node<T>>
+    fn split_insert(
+        data: &[T],
+        off: usize,
+        val: T,
+        pool: &mut NodePool<T>,
+    ) -> Ref<Node<T>>
     where
         T: Clone,
     {
@@ -717,11 +3097,9 @@ where
                 w
             }
         };
-        Ref::new(Node::M {
-            weight,
-            left: Ref::new(Node::Z { data: ld }),
-            right: Ref::new(Node::Z { data: rd }),
-        })
+        let left = pool.alloc(Node::Z { data: ld });
+        let right = pool.alloc(Node::Z { data: rd });
+        pool.newm(left, right, weight)
     }
 
     fn split_off(&self, off: usize, len: usize) -> (Ref<Node<T>>, Ref<Node<T>>, usize)
@@ -734,10 +3112,20 @@ where
                 right,
                 weight,
             } if off < *weight => {
+                // left sub-tree is used as is, instead of padding it with an
+                // empty leaf node, so that repeated splits don't accumulate
+                // empty leaf nodes.
                 let (left, root, n) = left.split_off(off, *weight);
                 let root = Node::newm(root, Ref::clone(right), n);
-                let node = Node::newm(left, Node::empty_leaf(), weight - n);
-                (node, root, n + (len - weight))
+                (left, root, n + (len - weight))
+            }
+            Node::M {
+                left,
+                right,
+                weight,
+            } if off == *weight => {
+                let root = Ref::clone(right);
+                (Ref::clone(left), root, len - weight)
             }
             Node::M {
                 left,
@@ -767,6 +3155,223 @@ where
         }
     }
 
+    #[cfg(any(feature = "ipld", test))]
+    fn merkle_hash(&self) -> [u8; 32]
+    where
+        T: std::hash::Hash,
+    {
+        self.merkle_hash_with(&mut |child| child.merkle_hash())
+    }
+
+    #[cfg(any(feature = "ipld", test))]
+    fn merkle_hash_cached(node: &Ref<Node<T>>, cache: &mut MerkleCache<T>) -> [u8; 32]
+    where
+        T: std::hash::Hash,
+    {
+        let key = Ref::as_ptr(node) as usize;
+        match cache.hashes.get(&key) {
+            Some((weak, hash)) if is_same_node(weak, node) => return *hash,
+            // stale entry, replaced below.
+            Some(_) | None => (),
+        }
+
+        let hash =
+            node.merkle_hash_with(&mut |child| Node::merkle_hash_cached(child, cache));
+        cache.hashes.insert(key, (Ref::downgrade(node), hash));
+        hash
+    }
+
+    #[cfg(any(feature = "ipld", test))]
+    fn merkle_hash_with(
+        &self,
+        child_hash: &mut dyn FnMut(&Ref<Node<T>>) -> [u8; 32],
+    ) -> [u8; 32]
+    where
+        T: std::hash::Hash,
+    {
+        use sha2::Digest;
+
+        let mut hasher = Sha256Hasher(sha2::Sha256::new());
+        match self {
+            Node::M {
+                weight,
+                left,
+                right,
+            } => {
+                hasher.0.update([1_u8]);
+                hasher.0.update(child_hash(left));
+                hasher.0.update(child_hash(right));
+                hasher.0.update((*weight as u64).to_le_bytes());
+            }
+            Node::Z { data } => {
+                hasher.0.update([0_u8]);
+                hasher.0.update((data.len() as u64).to_le_bytes());
+                data.iter().for_each(|item| item.hash(&mut hasher));
+            }
+        }
+        hasher.0.finalize().into()
+    }
+
+    // return number of items in this sub-tree, after verifying weights.
+    fn verify(&self) -> Result<usize> {
+        match self {
+            Node::M {
+                weight,
+                left,
+                right,
+            } => match left.verify()? {
+                n if n == *weight => Ok(n + right.verify()?),
+                n => err_at!(Fatal, msg: "weight mismatch {} != {}", weight, n),
+            },
+            Node::Z { data } => Ok(data.len()),
+        }
+    }
+
+    // return whether every node in the tree is uniquely owned.
+    fn is_unique(node: &mut Ref<Node<T>>) -> bool {
+        match Ref::get_mut(node) {
+            Some(Node::M { left, right, .. }) => {
+                Self::is_unique(left) && Self::is_unique(right)
+            }
+            Some(Node::Z { .. }) => true,
+            None => false,
+        }
+    }
+
+    // return whether every node on the path to `off` is uniquely owned.
+    fn is_path_unique(node: &mut Ref<Node<T>>, off: usize) -> bool {
+        match Ref::get_mut(node) {
+            Some(Node::M { weight, left, .. }) if off < *weight => {
+                Self::is_path_unique(left, off)
+            }
+            Some(Node::M { weight, right, .. }) => {
+                let off = off - *weight;
+                Self::is_path_unique(right, off)
+            }
+            Some(Node::Z { .. }) => true,
+            None => false,
+        }
+    }
+
+    // return the right-most leaf node's data, if every node on the path to
+    // it is uniquely owned.
+    fn last_leaf_mut(node: &mut Ref<Node<T>>) -> Option<&mut Vec<T>> {
+        match Ref::get_mut(node)? {
+            Node::M { right, .. } => Self::last_leaf_mut(right),
+            Node::Z { data } => Some(data),
+        }
+    }
+
+    fn for_each_leaf_mut<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&mut [T]),
+    {
+        match self {
+            Node::M { left, right, .. } => {
+                Ref::get_mut(left).unwrap().for_each_leaf_mut(f);
+                Ref::get_mut(right).unwrap().for_each_leaf_mut(f);
+            }
+            Node::Z { data } if data.is_empty() => (),
+            Node::Z { data } => f(data),
+        }
+    }
+
+    #[cfg(any(feature = "rayon", test))]
+    fn collect_leafs_mut<'a>(&'a mut self, acc: &mut Vec<&'a mut [T]>) {
+        match self {
+            Node::M { left, right, .. } => {
+                Ref::get_mut(left).unwrap().collect_leafs_mut(acc);
+                Ref::get_mut(right).unwrap().collect_leafs_mut(acc);
+            }
+            Node::Z { data } if data.is_empty() => (),
+            Node::Z { data } => acc.push(data),
+        }
+    }
+
+    fn reduce_balanced<A, F, G>(&self, leaf: &F, combine: &G) -> Option<A>
+    where
+        F: Fn(&[T]) -> A,
+        G: Fn(A, A) -> A,
+    {
+        match self {
+            Node::M { left, right, .. } => {
+                let a = left.reduce_balanced(leaf, combine);
+                let b = right.reduce_balanced(leaf, combine);
+                match (a, b) {
+                    (Some(a), Some(b)) => Some(combine(a, b)),
+                    (a, None) => a,
+                    (None, b) => b,
+                }
+            }
+            Node::Z { data } if data.is_empty() => None,
+            Node::Z { data } => Some(leaf(data)),
+        }
+    }
+
+    // `start` and `end` are relative to this sub-tree.
+    fn range_fold<A, F, G>(
+        &self,
+        start: usize,
+        end: usize,
+        leaf: &F,
+        combine: &G,
+    ) -> Option<A>
+    where
+        F: Fn(&[T]) -> A,
+        G: Fn(A, A) -> A,
+    {
+        match self {
+            _ if start >= end => None,
+            Node::M {
+                weight,
+                left,
+                right,
+            } => {
+                let a = match start < *weight {
+                    true => left.range_fold(start, end.min(*weight), leaf, combine),
+                    false => None,
+                };
+                let b = match end > *weight {
+                    true => {
+                        let start = start.saturating_sub(*weight);
+                        right.range_fold(start, end - weight, leaf, combine)
+                    }
+                    false => None,
+                };
+                match (a, b) {
+                    (Some(a), Some(b)) => Some(combine(a, b)),
+                    (a, None) => a,
+                    (None, b) => b,
+                }
+            }
+            Node::Z { data } => Some(leaf(&data[start..end])),
+        }
+    }
+
+    fn try_reduce_tree<A, E, F, G>(
+        &self,
+        leaf: &F,
+        combine: &G,
+    ) -> std::result::Result<Option<A>, E>
+    where
+        F: Fn(&[T]) -> A,
+        G: Fn(A, A) -> std::result::Result<A, E>,
+    {
+        match self {
+            Node::M { left, right, .. } => {
+                let a = left.try_reduce_tree(leaf, combine)?;
+                let b = right.try_reduce_tree(leaf, combine)?;
+                match (a, b) {
+                    (Some(a), Some(b)) => Ok(Some(combine(a, b)?)),
+                    (a, None) => Ok(a),
+                    (None, b) => Ok(b),
+                }
+            }
+            Node::Z { data } if data.is_empty() => Ok(None),
+            Node::Z { data } => Ok(Some(leaf(data))),
+        }
+    }
+
     fn auto_rebalance(
         node: Ref<Node<T>>,
         depth: usize,
@@ -785,11 +3390,21 @@ where
                 let mut leafs = Node::collect_leaf_nodes(node, packed, rn.leaf_cap);
                 leafs.reverse();
 
+                if let Some(f) = rn.on_rebalance.0.as_ref() {
+                    let n_leafs = leafs.len();
+                    f(RebalanceEvent {
+                        depth,
+                        n_leafs,
+                        packed,
+                    })
+                }
+
                 let depth = (leafs.len() as f64).log2().ceil() as usize;
                 let (nroot, _) = Node::build_bottoms_up(depth, &mut leafs);
                 debug_assert!(leafs.is_empty());
 
-                (nroot, depth)
+                // `depth` counts the intermediate levels, add the leaf level.
+                (nroot, depth + 1)
             }
         }
     }
@@ -894,98 +3509,653 @@ where
             }
         };
 
-        (root, n)
+        (root, n)
+    }
+
+    fn build_iter_stack<'a>(node: &'a Node<T>, iter: &mut Iter<'a, T>) {
+        match node {
+            Node::M { left, right, .. } => {
+                iter.stack.push(right);
+                Self::build_iter_stack(left, iter);
+            }
+            node @ Node::Z { .. } => {
+                iter.node = Some(node);
+            }
+        }
+    }
+
+    // nodes that are uniquely owned are unwrapped, so that leaf data can be
+    // moved out, while shared nodes are only referenced.
+    fn build_into_iter_stack(node: Ref<Node<T>>, iter: &mut IntoIter<T>) {
+        match Ref::try_unwrap(node) {
+            Ok(Node::M { left, right, .. }) => {
+                iter.stack.push(right);
+                Self::build_into_iter_stack(left, iter);
+            }
+            Ok(Node::Z { data }) => {
+                iter.leaf = Some(IntoLeaf::Owned(data.into_iter()));
+            }
+            Err(node) => match node.as_ref() {
+                Node::M { left, right, .. } => {
+                    let left = Ref::clone(left);
+                    iter.stack.push(Ref::clone(right));
+                    mem::drop(node);
+                    Self::build_into_iter_stack(left, iter);
+                }
+                Node::Z { .. } => {
+                    iter.leaf = Some(IntoLeaf::Shared(node, 0));
+                }
+            },
+        }
+    }
+
+    // only used with src/bin/fuzzy program
+    #[cfg(test)]
+    fn fetch_multiversions(&self, acc: &mut Vec<*const u8>) -> usize {
+        match self {
+            Node::M { left, right, .. } => {
+                if Ref::strong_count(left) > 1 {
+                    let ptr = Ref::as_ptr(left);
+                    acc.push(ptr as *const u8);
+                }
+                let mut n = left.fetch_multiversions(acc);
+
+                if Ref::strong_count(right) > 1 {
+                    let ptr = Ref::as_ptr(right);
+                    acc.push(ptr as *const u8);
+                }
+                n += right.fetch_multiversions(acc);
+                n + 1
+            }
+            Node::Z { .. } => 1,
+        }
+    }
+
+    fn describe_tree(&self, prefix: &str, len: usize, out: &mut String) {
+        match self {
+            Node::M {
+                left,
+                right,
+                weight,
+            } => {
+                out.push_str(&format!("{}nodem:{} weight:{}\n", prefix, len, weight));
+                let prefix = format!("{}  ", prefix);
+                left.describe_tree(&prefix, *weight, out);
+                right.describe_tree(&prefix, len - *weight, out);
+            }
+            Node::Z { data } => {
+                out.push_str(&format!("{}nodez:{}\n", prefix, data.len()));
+            }
+        }
+    }
+}
+
+struct Rebalance<'a> {
+    n_leafs: f64,
+    auto_rebalance: bool,
+    leaf_cap: usize,
+    on_rebalance: &'a RebalanceHook,
+}
+
+impl<'a> Rebalance<'a> {
+    // `on_rebalance` is borrowed apart from `r`, so that the vector's tree
+    // and pool can be borrowed mutably while this is alive.
+    fn new<T: Sized>(r: &Vector<T>, on_rebalance: &'a RebalanceHook) -> Rebalance<'a> {
+        let n_leafs = r.len / max_leaf_items::<T>(r.leaf_cap);
+        Rebalance {
+            n_leafs: n_leafs as f64,
+            auto_rebalance: r.auto_rebalance,
+            leaf_cap: r.leaf_cap,
+            on_rebalance,
+        }
+    }
+
+    fn can_rebalance(&self, depth: usize) -> bool {
+        match depth {
+            n if n < crate::REBALANCE_THRESHOLD => false,
+            _ if (depth as f64) > (self.n_leafs.log2() * 3_f64) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Memory usage of a vector, returned by `Vector::memory_report`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemoryReport {
+    /// Number of leaf nodes.
+    pub n_leafs: usize,
+    /// Depth of the tree, a tree with single leaf node has a depth of 1.
+    pub depth: usize,
+    /// Bytes used by intermediate nodes.
+    pub spine_bytes: usize,
+    /// Bytes used by leaf nodes, excluding its data.
+    pub leaf_bytes: usize,
+    /// Bytes used by elements stored in leaf nodes.
+    pub data_bytes: usize,
+    /// Bytes allocated for leaf nodes' data, including unused capacity.
+    pub data_capacity_bytes: usize,
+    /// Memory overhead, in percentage, over the bytes needed for elements
+    /// alone. Same as the `mem_ratio` computed by benchmarks.
+    pub overhead: f64,
+}
+
+impl MemoryReport {
+    fn total(&self) -> usize {
+        self.spine_bytes + self.leaf_bytes + self.data_capacity_bytes
+    }
+}
+
+/// Event passed to the callback set using `Vector::set_on_rebalance`, every
+/// time the tree is rebuilt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RebalanceEvent {
+    /// Depth that triggered the auto-rebalance, ZERO when
+    /// `Vector::rebalance` is called explicitly.
+    pub depth: usize,
+    /// Number of leaf nodes in the rebuilt tree.
+    pub n_leafs: usize,
+    /// Whether leaf nodes are packed while rebuilding the tree.
+    pub packed: bool,
+}
+
+#[derive(Clone, Default)]
+struct RebalanceHook(Option<Ref<RebalanceFn>>);
+
+impl fmt::Debug for RebalanceHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RebalanceHook({})", self.0.is_some())
+    }
+}
+
+/// Cache of merkle hashes, used by `Vector::merkle_root_cached` to avoid
+/// hashing sub-trees that are shared across versions of a vector.
+///
+/// Cache holds a weak reference to every hashed node, so that it does not
+/// keep old versions of a vector alive. Entries for dropped nodes are never
+/// reused, and can be removed using [MerkleCache::gc].
+///
+/// Weak references still count against in-place mutation, that is, nodes
+/// hashed using the cache are treated as shared by `_mut` methods, until
+/// the cache is cleared or dropped.
+#[cfg(any(feature = "ipld", test))]
+pub struct MerkleCache<T> {
+    hashes: std::collections::HashMap<usize, (Weak<Node<T>>, [u8; 32])>,
+}
+
+#[cfg(any(feature = "ipld", test))]
+impl<T> Default for MerkleCache<T> {
+    fn default() -> MerkleCache<T> {
+        MerkleCache {
+            hashes: std::collections::HashMap::default(),
+        }
+    }
+}
+
+#[cfg(any(feature = "ipld", test))]
+impl<T> MerkleCache<T> {
+    /// Return the number of nodes cached.
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Return whether cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Drop all cached hashes, along with the references to their nodes.
+    pub fn clear(&mut self) {
+        self.hashes.clear()
+    }
+
+    /// Drop cached hashes of nodes that are no longer part of any vector.
+    pub fn gc(&mut self) {
+        self.hashes.retain(|_, (weak, _)| weak.strong_count() > 0)
+    }
+}
+
+/// Cache of mapped leaf nodes, used by `Vector::map_cached` to avoid
+/// mapping leaf nodes that are shared across versions of a vector.
+///
+/// Cache holds a weak reference to every source leaf node, so that it does
+/// not keep old versions of a vector alive. Entries for dropped leaf nodes
+/// are never reused, and can be removed using [MapCache::gc].
+///
+/// Weak references still count against in-place mutation, that is, leaf
+/// nodes mapped using the cache are treated as shared by `_mut` methods,
+/// until the cache is cleared or dropped.
+pub struct MapCache<T, U> {
+    leafs: std::collections::HashMap<usize, MappedLeaf<T, U>>,
+}
+
+type MappedLeaf<T, U> = (Weak<Node<T>>, Ref<Node<U>>);
+
+impl<T, U> Default for MapCache<T, U> {
+    fn default() -> MapCache<T, U> {
+        MapCache {
+            leafs: std::collections::HashMap::default(),
+        }
+    }
+}
+
+impl<T, U> MapCache<T, U> {
+    /// Return the number of leaf nodes cached.
+    pub fn len(&self) -> usize {
+        self.leafs.len()
+    }
+
+    /// Return whether cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.leafs.is_empty()
+    }
+
+    /// Drop all mapped leaf nodes, along with the references to their
+    /// source leaf nodes.
+    pub fn clear(&mut self) {
+        self.leafs.clear()
+    }
+
+    /// Drop mapped leaf nodes whose source leaf nodes are no longer part of
+    /// any vector.
+    pub fn gc(&mut self) {
+        self.leafs.retain(|_, (weak, _)| weak.strong_count() > 0)
+    }
+}
+
+/// Cache of leaf nodes, used by `Vector::deserialize_structured` to share
+/// identical leaf nodes across deserialized versions of a vector.
+///
+/// Interner holds a weak reference to every interned leaf node, so that it
+/// does not keep old versions of a vector alive. Entries for dropped leaf
+/// nodes are never reused, and can be removed using [LeafInterner::gc].
+///
+/// Weak references still count against in-place mutation, that is, while
+/// the interner is alive, deserialized vectors are treated as shared by
+/// `_mut` methods. Clear or drop the interner before calling them. Note
+/// that identical leaf nodes within the same vector are also shared.
+#[cfg(any(feature = "serde", test))]
+pub struct LeafInterner<T> {
+    leafs: std::collections::HashMap<u64, Vec<Weak<Node<T>>>>,
+}
+
+#[cfg(any(feature = "serde", test))]
+impl<T> Default for LeafInterner<T> {
+    fn default() -> LeafInterner<T> {
+        LeafInterner {
+            leafs: std::collections::HashMap::default(),
+        }
+    }
+}
+
+#[cfg(any(feature = "serde", test))]
+impl<T> LeafInterner<T> {
+    /// Return the number of leaf nodes interned.
+    pub fn len(&self) -> usize {
+        self.leafs.values().map(|leafs| leafs.len()).sum()
+    }
+
+    /// Return whether interner is empty.
+    pub fn is_empty(&self) -> bool {
+        self.leafs.is_empty()
+    }
+
+    /// Drop all references to interned leaf nodes.
+    pub fn clear(&mut self) {
+        self.leafs.clear()
+    }
+
+    /// Drop references to interned leaf nodes that are no longer part of
+    /// any vector.
+    pub fn gc(&mut self) {
+        self.leafs.retain(|_, leafs| {
+            leafs.retain(|weak| weak.strong_count() > 0);
+            !leafs.is_empty()
+        })
+    }
+}
+
+#[cfg(any(feature = "serde", test))]
+impl<T> LeafInterner<T>
+where
+    T: std::hash::Hash + Eq,
+{
+    fn intern(&mut self, data: Vec<T>) -> Ref<Node<T>> {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        data.hash(&mut hasher);
+
+        let leafs = self.leafs.entry(hasher.finish()).or_default();
+        let leaf =
+            leafs
+                .iter()
+                .filter_map(Weak::upgrade)
+                .find(|leaf| match leaf.as_ref() {
+                    Node::Z { data: other } => other == &data,
+                    _ => unreachable!(),
+                });
+        match leaf {
+            Some(leaf) => leaf,
+            None => {
+                let leaf = Ref::new(Node::Z { data });
+                leafs.retain(|weak| weak.strong_count() > 0);
+                leafs.push(Ref::downgrade(&leaf));
+                leaf
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "serde", test))]
+#[derive(serde::Serialize)]
+#[serde(rename = "Vector")]
+struct SerVector<'a, T> {
+    len: usize,
+    leaf_cap: usize,
+    auto_rebalance: bool,
+    root: SerNode<'a, T>,
+}
+
+#[cfg(any(feature = "serde", test))]
+struct SerNode<'a, T>(&'a Node<T>);
+
+#[cfg(any(feature = "serde", test))]
+impl<'a, T> serde::Serialize for SerNode<'a, T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStructVariant;
+
+        match self.0 {
+            Node::M {
+                weight,
+                left,
+                right,
+            } => {
+                let mut sv = serializer.serialize_struct_variant("Node", 0, "M", 3)?;
+                sv.serialize_field("weight", weight)?;
+                sv.serialize_field("left", &SerNode(left.as_ref()))?;
+                sv.serialize_field("right", &SerNode(right.as_ref()))?;
+                sv.end()
+            }
+            Node::Z { data } => {
+                let mut sv = serializer.serialize_struct_variant("Node", 1, "Z", 1)?;
+                sv.serialize_field("data", data)?;
+                sv.end()
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "serde", test))]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Vector")]
+struct DeVector<T> {
+    len: usize,
+    leaf_cap: usize,
+    auto_rebalance: bool,
+    root: DeNode<T>,
+}
+
+#[cfg(any(feature = "serde", test))]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Node")]
+enum DeNode<T> {
+    M {
+        weight: usize,
+        left: Box<DeNode<T>>,
+        right: Box<DeNode<T>>,
+    },
+    Z {
+        data: Vec<T>,
+    },
+}
+
+#[cfg(any(feature = "serde", test))]
+impl<T> DeNode<T>
+where
+    T: std::hash::Hash + Eq,
+{
+    // return (node, number of items under node), or error message if weight
+    // of an intermediate node does not match its left sub-tree, or if a leaf
+    // node holds more than `max_items`.
+    fn into_node(
+        self,
+        interner: &mut LeafInterner<T>,
+        max_items: usize,
+    ) -> std::result::Result<(Ref<Node<T>>, usize), String> {
+        match self {
+            DeNode::M {
+                weight,
+                left,
+                right,
+            } => {
+                let (left, n) = left.into_node(interner, max_items)?;
+                if n != weight {
+                    return Err(format!("weight mismatch {} != {}", weight, n));
+                }
+                let (right, m) = right.into_node(interner, max_items)?;
+                Ok((Node::newm(left, right, weight), weight + m))
+            }
+            DeNode::Z { data } if data.len() > max_items => Err(format!(
+                "leaf node with {} items > {}",
+                data.len(),
+                max_items
+            )),
+            DeNode::Z { data } => {
+                let n = data.len();
+                Ok((interner.intern(data), n))
+            }
+        }
+    }
+}
+
+// Adapter to feed `std::hash::Hash` types into SHA-256.
+#[cfg(any(feature = "ipld", test))]
+struct Sha256Hasher(sha2::Sha256);
+
+#[cfg(any(feature = "ipld", test))]
+impl std::hash::Hasher for Sha256Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        sha2::Digest::update(&mut self.0, bytes)
+    }
+
+    // integers are hashed as fixed-width little-endian bytes, instead of the
+    // default native-endian and platform-width bytes.
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64)
+    }
+
+    fn finish(&self) -> u64 {
+        let digest = sha2::Digest::finalize(self.0.clone());
+        let mut bytes = [0_u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        u64::from_be_bytes(bytes)
+    }
+}
+
+// Accumulate items into packed leaf nodes, to build a new balanced tree
+// in a single pass.
+struct LeafBuilder<T> {
+    leaf_cap: usize,
+    max_items: usize,
+    data: Vec<T>,
+    leafs: Vec<Ref<Node<T>>>,
+    len: usize,
+}
+
+impl<T> LeafBuilder<T> {
+    fn new(leaf_cap: usize) -> LeafBuilder<T> {
+        LeafBuilder {
+            leaf_cap,
+            max_items: max_leaf_items::<T>(leaf_cap),
+            data: Vec::with_capacity(max_leaf_items::<T>(leaf_cap)),
+            leafs: Vec::default(),
+            len: 0,
+        }
     }
 
-    fn build_iter_stack<'a, 'b>(node: &'a Node<T>, iter: &'b mut Iter<'a, T>) {
-        match node {
-            Node::M { left, right, .. } => {
-                iter.stack.push(right);
-                Self::build_iter_stack(left, iter);
-            }
-            node @ Node::Z { .. } => {
-                iter.node = Some(node);
-            }
+    fn push(&mut self, item: T) {
+        if self.data.len() >= self.max_items {
+            let data = Vec::with_capacity(self.max_items);
+            let mut data = mem::replace(&mut self.data, data);
+            data.shrink_to_fit();
+            self.leafs.push(Ref::new(Node::Z { data }));
         }
+        self.data.push(item);
+        self.len += 1;
     }
 
-    fn build_into_iter_stack(node: &Ref<Node<T>>, iter: &mut IntoIter<T>) {
-        match node.as_ref() {
-            Node::M { left, right, .. } => {
-                iter.stack.push(Ref::clone(right));
-                Self::build_into_iter_stack(left, iter);
-            }
-            Node::Z { .. } => {
-                iter.node = Some(Ref::clone(node));
-            }
+    // share `leaf` as is, after the elements pushed so far.
+    fn push_leaf(&mut self, leaf: Ref<Node<T>>) {
+        if !self.data.is_empty() {
+            let mut data = mem::take(&mut self.data);
+            data.shrink_to_fit();
+            self.leafs.push(Ref::new(Node::Z { data }));
         }
+        self.len += leaf.len();
+        self.leafs.push(leaf);
     }
 
-    // only used with src/bin/fuzzy program
-    #[cfg(test)]
-    fn fetch_multiversions(&self, acc: &mut Vec<*const u8>) -> usize {
-        match self {
-            Node::M { left, right, .. } => {
-                if Ref::strong_count(left) > 1 {
-                    let ptr = Ref::as_ptr(left);
-                    acc.push(ptr as *const u8);
-                }
-                let mut n = left.fetch_multiversions(acc);
+    fn build(mut self, auto_rebalance: bool) -> Vector<T> {
+        if !self.data.is_empty() {
+            let mut data = mem::take(&mut self.data);
+            data.shrink_to_fit();
+            self.leafs.push(Ref::new(Node::Z { data }));
+        }
+        self.leafs.reverse();
 
-                if Ref::strong_count(right) > 1 {
-                    let ptr = Ref::as_ptr(right);
-                    acc.push(ptr as *const u8);
-                }
-                n += right.fetch_multiversions(acc);
-                n + 1
-            }
-            Node::Z { .. } => 1,
+        let depth = (self.leafs.len() as f64).log2().ceil() as usize;
+        let (root, _) = Node::build_bottoms_up(depth, &mut self.leafs);
+        debug_assert!(self.leafs.is_empty());
+
+        Vector {
+            len: self.len,
+            root,
+            auto_rebalance,
+            leaf_cap: self.leaf_cap,
+            pool: NodePool::default(),
+            on_rebalance: RebalanceHook::default(),
+            debug_checks: false,
         }
     }
+}
 
-    #[cfg(test)]
-    #[allow(dead_code)]
-    fn pretty_print(&self, mut prefix: String, len: usize) {
-        match self {
-            Node::M {
-                left,
-                right,
-                weight,
-            } => {
-                println!("{}nodem:{}", prefix, len);
-                prefix.push_str("  ");
-                left.pretty_print(prefix.clone(), *weight);
-                right.pretty_print(prefix, len - *weight);
-            }
-            Node::Z { data } => {
-                println!("{}nodez:{}", prefix, data.len());
-            }
+// Free-list of node allocations, recycled across copy-on-write insert and
+// remove. Only nodes that are uniquely owned by the vector are recycled,
+// nodes shared with other versions of the vector are left untouched.
+//
+// Enabled with the `pool` feature, otherwise nodes are allocated and
+// dropped as usual, and the pool is zero-sized.
+#[derive(Debug)]
+struct NodePool<T> {
+    #[cfg(feature = "pool")]
+    nodes: Vec<Ref<Node<T>>>,
+    #[cfg(not(feature = "pool"))]
+    nodes: std::marker::PhantomData<Ref<Node<T>>>,
+}
+
+impl<T> Default for NodePool<T> {
+    fn default() -> NodePool<T> {
+        NodePool {
+            nodes: Default::default(),
         }
     }
 }
 
-struct Rebalance {
-    n_leafs: f64,
-    auto_rebalance: bool,
-    leaf_cap: usize,
-}
+impl<T> NodePool<T> {
+    #[cfg(feature = "pool")]
+    const MAX_NODES: usize = 256;
 
-impl Rebalance {
-    fn new<T: Sized>(r: &Vector<T>) -> Rebalance {
-        let n_leafs = r.len / max_leaf_items::<T>(r.leaf_cap);
-        Rebalance {
-            n_leafs: n_leafs as f64,
-            auto_rebalance: r.auto_rebalance,
-            leaf_cap: r.leaf_cap,
+    fn alloc(&mut self, node: Node<T>) -> Ref<Node<T>> {
+        #[cfg(feature = "pool")]
+        if let Some(mut item) = self.nodes.pop() {
+            *Ref::get_mut(&mut item).unwrap() = node;
+            return item;
         }
+        Ref::new(node)
     }
 
-    fn can_rebalance(&self, depth: usize) -> bool {
-        match depth {
-            n if n < crate::REBALANCE_THRESHOLD => false,
-            _ if (depth as f64) > (self.n_leafs.log2() * 3_f64) => true,
-            _ => false,
+    fn newm(
+        &mut self,
+        left: Ref<Node<T>>,
+        right: Ref<Node<T>>,
+        weight: usize,
+    ) -> Ref<Node<T>> {
+        self.alloc(Node::M {
+            left,
+            right,
+            weight,
+        })
+    }
+
+    // Recycle the nodes of an older root that are no more shared. Walk stops
+    // at the first shared node on every path, hence the cost is proportional
+    // to the number of nodes replaced by the copy-on-write operation.
+    fn recycle(&mut self, root: Ref<Node<T>>) {
+        #[cfg(feature = "pool")]
+        {
+            let mut stack = vec![root];
+            while let Some(mut node) = stack.pop() {
+                let inner = match Ref::get_mut(&mut node) {
+                    Some(inner) => inner,
+                    None => continue,
+                };
+                let empty = Node::Z {
+                    data: Vec::default(),
+                };
+                if let Node::M { left, right, .. } = mem::replace(inner, empty) {
+                    stack.push(left);
+                    stack.push(right);
+                }
+                if self.nodes.len() < Self::MAX_NODES {
+                    self.nodes.push(node);
+                }
+            }
         }
+        #[cfg(not(feature = "pool"))]
+        mem::drop(root);
     }
 }
 
@@ -998,6 +4168,17 @@ pub struct Iter<'a, T> {
     off: usize,
 }
 
+// derive would require `T: Clone`, while only references are cloned.
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            stack: self.stack.clone(),
+            node: self.node,
+            off: self.off,
+        }
+    }
+}
+
 impl<'a, T> Iter<'a, T> {
     fn new(root: &'a Node<T>) -> Iter<'a, T> {
         let mut iter = Iter {
@@ -1008,6 +4189,42 @@ impl<'a, T> Iter<'a, T> {
         Node::build_iter_stack(root, &mut iter);
         iter
     }
+
+    fn new_at(root: &'a Node<T>, off: usize) -> Iter<'a, T> {
+        let mut iter = Iter {
+            stack: Vec::default(),
+            node: None,
+            off: 0,
+        };
+        iter.seek(root, off);
+        iter
+    }
+
+    // descend to the leaf node containing `off`, pushing the right
+    // siblings on the way, so that iteration resumes from `off`.
+    fn seek(&mut self, mut node: &'a Node<T>, mut off: usize) {
+        loop {
+            match node {
+                Node::M {
+                    weight,
+                    left,
+                    right,
+                } if off < *weight => {
+                    self.stack.push(right);
+                    node = left;
+                }
+                Node::M { weight, right, .. } => {
+                    off -= *weight;
+                    node = right;
+                }
+                Node::Z { .. } => {
+                    self.node = Some(node);
+                    self.off = off;
+                    break;
+                }
+            }
+        }
+    }
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
@@ -1031,16 +4248,140 @@ impl<'a, T> Iterator for Iter<'a, T> {
             Some(_) => unreachable!(),
         }
     }
+
+    // skip whole sub-trees, instead of single elements, and descend to the
+    // leaf node containing the n-th element.
+    fn nth(&mut self, mut n: usize) -> Option<&'a T> {
+        if let Some(Node::Z { data }) = self.node {
+            let remaining = data.len() - self.off.min(data.len());
+            if n < remaining {
+                self.off += n;
+                return self.next();
+            }
+            n -= remaining;
+            self.off = data.len();
+        }
+
+        while let Some(node) = self.stack.pop() {
+            match node.len() {
+                len if n < len => {
+                    self.seek(node, n);
+                    return self.next();
+                }
+                len => n -= len,
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over elements of a Vector, from the last element to the
+/// first.
+///
+/// Created by the rev_iter method on Vector.
+pub struct RevIter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+    node: Option<&'a Node<T>>,
+    // number of elements yet to be yielded from `node`.
+    off: usize,
+}
+
+impl<'a, T> Clone for RevIter<'a, T> {
+    fn clone(&self) -> Self {
+        RevIter {
+            stack: self.stack.clone(),
+            node: self.node,
+            off: self.off,
+        }
+    }
+}
+
+impl<'a, T> RevIter<'a, T> {
+    fn new(root: &'a Node<T>) -> RevIter<'a, T> {
+        let mut iter = RevIter {
+            stack: Vec::default(),
+            node: None,
+            off: 0,
+        };
+        iter.descend(root);
+        iter
+    }
+
+    // descend to the right-most leaf node, pushing the left siblings on the
+    // way.
+    fn descend(&mut self, mut node: &'a Node<T>) {
+        loop {
+            match node {
+                Node::M { left, right, .. } => {
+                    self.stack.push(left);
+                    node = right;
+                }
+                Node::Z { data } => {
+                    self.node = Some(node);
+                    self.off = data.len();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for RevIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match self.node {
+                Some(Node::Z { data }) if self.off > 0 => {
+                    self.off -= 1;
+                    break Some(&data[self.off]);
+                }
+                Some(Node::Z { .. }) | None => match self.stack.pop() {
+                    Some(node) => self.descend(node),
+                    None => break None,
+                },
+                Some(_) => unreachable!(),
+            }
+        }
+    }
 }
 
 /// An iterator that moves elements out of Vector.
 ///
 /// Created by the into_iter method on Vector (provided by the
-/// IntoIterator trait).
+/// IntoIterator trait). Elements are moved out of leaf nodes that are
+/// uniquely owned by the vector, and cloned from leaf nodes that are
+/// shared with other versions of the vector.
 pub struct IntoIter<T> {
     stack: Vec<Ref<Node<T>>>,
-    node: Option<Ref<Node<T>>>,
-    off: usize,
+    leaf: Option<IntoLeaf<T>>,
+}
+
+enum IntoLeaf<T> {
+    Owned(std::vec::IntoIter<T>),
+    Shared(Ref<Node<T>>, usize),
+}
+
+/// Cloning shares the remaining leaf nodes between both iterators, hence
+/// elements from them are cloned rather than moved. Elements remaining in
+/// the current leaf node, if owned, are cloned.
+impl<T> Clone for IntoIter<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        let leaf = match &self.leaf {
+            Some(IntoLeaf::Owned(iter)) => Some(IntoLeaf::Owned(iter.clone())),
+            Some(IntoLeaf::Shared(node, off)) => {
+                Some(IntoLeaf::Shared(Ref::clone(node), *off))
+            }
+            None => None,
+        };
+        IntoIter {
+            stack: self.stack.clone(),
+            leaf,
+        }
+    }
 }
 
 impl<T> Iterator for IntoIter<T>
@@ -1050,22 +4391,225 @@ where
     type Item = T;
 
     fn next(&mut self) -> Option<T> {
-        match self.node.as_ref().map(|x| x.as_ref()) {
-            Some(Node::Z { data }) if self.off < data.len() => {
-                let item = data[self.off].clone();
-                self.off += 1;
-                Some(item)
-            }
-            Some(Node::Z { .. }) | None => match self.stack.pop() {
-                Some(node) => {
-                    self.off = 0;
-                    Node::build_into_iter_stack(&node, self);
-                    self.next()
+        loop {
+            match self.leaf.as_mut() {
+                Some(IntoLeaf::Owned(iter)) => {
+                    if let Some(item) = iter.next() {
+                        break Some(item);
+                    }
                 }
-                None => None,
-            },
-            Some(_) => unreachable!(),
+                Some(IntoLeaf::Shared(node, off)) => match node.as_ref() {
+                    Node::Z { data } if *off < data.len() => {
+                        let item = data[*off].clone();
+                        *off += 1;
+                        break Some(item);
+                    }
+                    Node::Z { .. } => (),
+                    _ => unreachable!(),
+                },
+                None => (),
+            }
+
+            self.leaf = None;
+            match self.stack.pop() {
+                Some(node) => Node::build_into_iter_stack(node, self),
+                None => break None,
+            }
+        }
+    }
+}
+
+/// Guard for in-place mutation of a vector under single ownership.
+///
+/// Created by the as_unique_mut method on Vector.
+pub struct UniqueMut<'a, T> {
+    arr: &'a mut Vector<T>,
+}
+
+impl<'a, T> UniqueMut<'a, T>
+where
+    T: Clone,
+{
+    /// Return the number of elements in the vector.
+    pub fn len(&self) -> usize {
+        self.arr.len
+    }
+
+    /// Return whether the vector is empty.
+    pub fn is_empty(&self) -> bool {
+        self.arr.len == 0
+    }
+
+    /// Refer to [Vector::get].
+    pub fn get(&self, index: usize) -> Result<&T> {
+        self.arr.get(index)
+    }
+
+    /// Return a mutable reference to the element at that position or
+    /// `IndexFail` error if out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Result<&mut T> {
+        match index < self.arr.len {
+            true => Ref::get_mut(&mut self.arr.root).unwrap().get_mut(index),
+            false => {
+                err_at!(IndexFail, msg: "index {} out of bounds for length {}", index, self.arr.len)
+            }
+        }
+    }
+
+    /// Refer to [Vector::insert_mut].
+    pub fn insert_mut(&mut self, off: usize, value: T) -> Result<()> {
+        self.arr.insert_mut(off, value)
+    }
+
+    /// Refer to [Vector::update_mut].
+    pub fn update_mut(&mut self, off: usize, value: T) -> Result<T> {
+        self.arr.update_mut(off, value)
+    }
+
+    /// Refer to [Vector::remove_mut].
+    pub fn remove_mut(&mut self, off: usize) -> Result<T> {
+        self.arr.remove_mut(off)
+    }
+}
+
+/// An iterator over a vector in non-overlapping chunks of `size` elements.
+///
+/// Created by the chunks_exact method on Vector.
+pub struct ChunksExact<'a, T> {
+    arr: &'a Vector<T>,
+    off: usize,
+    end: usize,
+    size: usize,
+}
+
+impl<'a, T> ChunksExact<'a, T>
+where
+    T: Clone,
+{
+    /// Return the remaining elements, that are less than `size`, that
+    /// shall not be returned by this iterator.
+    pub fn remainder(&self) -> Vector<T> {
+        self.arr.slice(self.end, self.arr.len)
+    }
+}
+
+impl<'a, T> Iterator for ChunksExact<'a, T>
+where
+    T: Clone,
+{
+    type Item = Vector<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.off {
+            off if off < self.end => {
+                self.off += self.size;
+                Some(self.arr.slice(off, self.off))
+            }
+            _ => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = (self.end - self.off) / self.size;
+        (n, Some(n))
+    }
+}
+
+/// A window of elements returned by [WindowsRef].
+#[derive(Debug)]
+pub enum WindowRef<'a, T> {
+    /// Window falls within a single leaf node.
+    Borrowed(&'a [T]),
+    /// Window straddles leaf nodes, as a sub-vector sharing the leaf nodes.
+    Owned(Vector<T>),
+}
+
+/// An iterator over a vector in overlapping windows of `size` elements.
+///
+/// Created by the windows_ref method on Vector.
+pub struct WindowsRef<'a, T> {
+    arr: &'a Vector<T>,
+    off: usize,
+    size: usize,
+    // leaf node containing `off`, and the position of its first element.
+    leaf: &'a [T],
+    leaf_off: usize,
+}
+
+impl<'a, T> Iterator for WindowsRef<'a, T>
+where
+    T: Clone,
+{
+    type Item = WindowRef<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (off, end) = (self.off, self.off + self.size);
+        if end > self.arr.len {
+            return None;
+        }
+
+        if off >= self.leaf_off + self.leaf.len() {
+            let (leaf, n) = self.arr.root.get_leaf(off);
+            self.leaf = leaf;
+            self.leaf_off = off - n;
         }
+        self.off += 1;
+
+        let item = match end - self.leaf_off {
+            n if n <= self.leaf.len() => {
+                WindowRef::Borrowed(&self.leaf[(off - self.leaf_off)..n])
+            }
+            _ => WindowRef::Owned(self.arr.slice(off, end)),
+        };
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = (self.arr.len + 1).saturating_sub(self.off + self.size);
+        (n, Some(n))
+    }
+}
+
+// convert range into [start, end) offsets, or `IndexFail` error if range is
+// out of bounds for `len`.
+fn to_start_end<R>(range: R, len: usize) -> Result<(usize, usize)>
+where
+    R: RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(n) => *n,
+        Bound::Excluded(n) => n.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(n) => n.saturating_add(1),
+        Bound::Excluded(n) => *n,
+        Bound::Unbounded => len,
+    };
+
+    if start > end || end > len {
+        err_at!(IndexFail, msg: "range {}..{} out of bounds for length {}", start, end, len)
+    } else {
+        Ok((start, end))
+    }
+}
+
+// Copy `items` into a leaf node's data, holding at most `n` elements. Full
+// leaf nodes are split on insert, hence sized exactly, while partially
+// filled leaf nodes are given a little slack, so that the next few in-place
+// inserts don't reallocate.
+fn leaf_data<T: Clone>(items: &[T], n: usize) -> Vec<T> {
+    let m = items.len();
+    let mut data = Vec::with_capacity(std::cmp::min(n, m + m / 4 + 1));
+    data.extend_from_slice(items);
+    data
+}
+
+// return whether `weak` still refers to `node`.
+fn is_same_node<T>(weak: &Weak<Node<T>>, node: &Ref<Node<T>>) -> bool {
+    match weak.upgrade() {
+        Some(val) => Ref::ptr_eq(&val, node),
+        None => false,
     }
 }
 
@@ -1084,6 +4628,7 @@ where
 
     debug_assert_eq!(refv.len(), arr.len());
     debug_assert_eq!(arr.len(), arr.root.len());
+    debug_assert!(arr.verify().is_ok());
 
     for (off, val) in refv.iter().enumerate() {
         debug_assert_eq!(arr.get(off).unwrap(), val, "off-{}", off);
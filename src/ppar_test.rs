@@ -7,6 +7,16 @@ fn test_new() {
     let arr: Vector<u64> = Vector::default();
     assert!(arr.is_empty());
     println!("test_new is thread-safe {}", arr.is_thread_safe());
+
+    let arr: Vector<u64> = Vector::new();
+    assert!(arr.is_empty());
+    assert_eq!(arr.footprint(), Vector::<u64>::default().footprint());
+
+    let arr: Vector<u64> = Vector::new()
+        .with_leaf_size(1024)
+        .with_auto_rebalance(false);
+    assert_eq!(arr.leaf_cap, 1024);
+    assert!(!arr.auto_rebalance);
 }
 
 #[test]
@@ -133,6 +143,30 @@ fn test_append() {
     }
 }
 
+#[test]
+fn test_append_rebalance() {
+    let seed: u64 = random();
+    println!("test_append_rebalance seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut refv: Vec<u64> = vec![];
+    let mut x: Vector<u64> = Vector::new();
+    let mut y: Vector<u64> = Vector::new();
+    for _ in 0..1000 {
+        let vals: Vec<u64> = (0..rng.gen::<usize>() % 100).map(|_| rng.gen()).collect();
+        refv.extend_from_slice(&vals);
+        x.append(Vector::from_slice(&vals, None));
+        y.append_no_rebalance(Vector::from_slice(&vals, None));
+    }
+
+    let n_leafs = (refv.len() / max_leaf_items::<u64>(crate::LEAF_CAP)) as f64;
+    let max_depth = (n_leafs.log2() * 3_f64).max(crate::REBALANCE_THRESHOLD as f64);
+    assert!(x.root.spine_depth() <= (max_depth as usize) + 1);
+    assert_eq!(y.root.spine_depth(), 1001);
+    validate(&x, &refv);
+    validate(&y, &refv);
+}
+
 #[test]
 fn test_prepend() {
     let seed: u64 = random();
@@ -295,3 +329,2963 @@ fn test_not_equal_if_different_length_but_same_prefix() {
 
     assert_eq!(v1, v2);
 }
+
+#[test]
+fn test_partition() {
+    let seed: u64 = random();
+    println!("test_partition seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        let (a, b) = arr.partition(|x| x % 3 == 0);
+        let (refa, refb): (Vec<u64>, Vec<u64>) = vals.iter().partition(|x| *x % 3 == 0);
+
+        validate(&a, &refa);
+        validate(&b, &refb);
+        validate(&arr, &vals);
+    }
+}
+
+#[test]
+fn test_zip_with() {
+    let seed: u64 = random();
+    println!("test_zip_with seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..10 {
+        let (n, m) = (rng.gen::<usize>() % 10_000, rng.gen::<usize>() % 10_000);
+        let xs: Vec<u64> = (0..n).map(|_| rng.gen::<u32>() as u64).collect();
+        let ys: Vec<u32> = (0..m).map(|_| rng.gen()).collect();
+        let (a, b) = (
+            Vector::from_slice(&xs, Some(64)),
+            Vector::from_slice(&ys, None),
+        );
+
+        let refv: Vec<u64> = xs
+            .iter()
+            .zip(ys.iter())
+            .map(|(x, y)| x + (*y as u64))
+            .collect();
+        let arr = a.zip_with(&b, |x, y| x + (*y as u64));
+        validate(&arr, &refv);
+
+        match a.try_zip_with(&b, |x, y| x + (*y as u64)) {
+            Ok(arr) if n == m => validate(&arr, &refv),
+            Ok(_) => panic!("expected length mismatch {} {}", n, m),
+            Err(_) => assert!(n != m),
+        }
+    }
+}
+
+#[test]
+fn test_scan() {
+    let seed: u64 = random();
+    println!("test_scan seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen::<u32>() as u64).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        let refv: Vec<u64> = vals
+            .iter()
+            .scan(0, |acc, x| {
+                *acc += x;
+                Some(*acc)
+            })
+            .collect();
+        validate(&arr.scan(0, |acc, x| acc + x), &refv);
+    }
+}
+
+#[test]
+fn test_split_off_by() {
+    let seed: u64 = random();
+    println!("test_split_off_by seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+
+        let k = rng.gen::<u64>() % (*n + 10);
+        let a = arr.split_off_by(|x| *x >= k);
+        let b = refv.split_off((k as usize).min(refv.len()));
+        validate(&a, &b);
+        validate(&arr, &refv);
+    }
+}
+
+#[test]
+fn test_insert_many() {
+    let seed: u64 = random();
+    println!("test_insert_many seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut arr = Vector::default();
+    arr.set_leaf_size(128);
+    let mut refv: Vec<u64> = vec![];
+
+    for _ in 0..100 {
+        let off = rng.gen::<usize>() % (arr.len() + 1);
+        let items: Vec<u64> = (0..(rng.gen::<usize>() % 1000))
+            .map(|_| rng.gen())
+            .collect();
+
+        arr.insert_many(off, items.clone()).unwrap();
+        let tail = refv.split_off(off);
+        refv.extend_from_slice(&items);
+        refv.extend_from_slice(&tail);
+    }
+    validate(&arr, &refv);
+
+    assert!(arr.insert_many(arr.len() + 1, vec![1, 2, 3]).is_err());
+    validate(&arr.rebalance(true).unwrap(), &refv);
+}
+
+#[test]
+fn test_remove_range() {
+    let seed: u64 = random();
+    println!("test_remove_range seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut refv: Vec<u64> = (0..100_000).map(|_| rng.gen()).collect();
+    let mut arr = Vector::from_slice(&refv, Some(1024));
+
+    while !arr.is_empty() {
+        let start = rng.gen::<usize>() % arr.len();
+        let end = start + (rng.gen::<usize>() % (arr.len() - start + 1));
+
+        let a = arr.remove_range(start..end).unwrap();
+        let b: Vec<u64> = refv.drain(start..end).collect();
+        assert!(a.iter().eq(b.iter()));
+        let a = a.rebalance(true).unwrap();
+        arr = arr.rebalance(true).unwrap();
+        validate(&a, &b);
+        validate(&arr, &refv);
+    }
+
+    assert!(arr.remove_range(..1).is_err());
+    assert!(arr.remove_range(..).unwrap().is_empty());
+}
+
+#[test]
+fn test_min_max() {
+    let seed: u64 = random();
+    println!("test_min_max seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<(u8, u64)> = (0..*n).map(|_| (rng.gen(), rng.gen())).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        assert_eq!(arr.min(), vals.iter().min());
+        assert_eq!(arr.max(), vals.iter().max());
+        assert_eq!(
+            arr.min_by(|a, b| a.1.cmp(&b.1)),
+            vals.iter().min_by(|a, b| a.1.cmp(&b.1))
+        );
+        assert_eq!(
+            arr.max_by(|a, b| a.1.cmp(&b.1)),
+            vals.iter().max_by(|a, b| a.1.cmp(&b.1))
+        );
+        assert_eq!(arr.min_by_key(|x| x.0), vals.iter().min_by_key(|x| x.0));
+        assert_eq!(arr.max_by_key(|x| x.0), vals.iter().max_by_key(|x| x.0));
+    }
+}
+
+#[test]
+fn test_sum_product() {
+    let seed: u64 = random();
+    println!("test_sum_product seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen::<u32>() as u64).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+        assert_eq!(arr.sum::<u64>(), vals.iter().sum::<u64>());
+
+        let vals: Vec<u64> = (0..(*n % 20)).map(|_| rng.gen::<u64>() % 8).collect();
+        let arr = Vector::from_slice(&vals, Some(16));
+        assert_eq!(arr.product::<u64>(), vals.iter().product::<u64>());
+    }
+}
+
+#[test]
+fn test_reduce_balanced() {
+    let seed: u64 = random();
+    println!("test_reduce_balanced seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen::<u32>() as u64).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        let sum = arr.reduce_balanced(|xs| xs.iter().sum::<u64>(), |a, b| a + b);
+        let refv = vals.iter().copied().reduce(|a, b| a + b);
+        assert_eq!(sum, refv);
+
+        let max = arr.reduce_balanced(|xs| *xs.iter().max().unwrap(), |a, b| a.max(b));
+        assert_eq!(max, vals.iter().copied().max());
+    }
+}
+
+#[test]
+fn test_get_unchecked() {
+    let seed: u64 = random();
+    println!("test_get_unchecked seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let vals: Vec<u64> = (0..100_000).map(|_| rng.gen()).collect();
+    let arr = Vector::from_slice(&vals, Some(128));
+    for (off, val) in vals.iter().enumerate() {
+        assert_eq!(unsafe { arr.get_unchecked(off) }, val);
+    }
+}
+
+#[test]
+fn test_into_iter_move() {
+    use std::rc::Rc;
+
+    let vals: Vec<Rc<u64>> = (0..10_000).map(Rc::new).collect();
+    let arr = Vector::from_slice(&vals, Some(128));
+    mem::drop(vals);
+
+    // shared leaf nodes are cloned.
+    let mut iter = arr.clone().into_iter();
+    assert_eq!(Rc::strong_count(&iter.next().unwrap()), 2);
+    mem::drop(iter);
+
+    // uniquely owned leaf nodes are moved.
+    let mut iter = arr.into_iter();
+    assert_eq!(Rc::strong_count(&iter.next().unwrap()), 1);
+    for (i, item) in iter.enumerate() {
+        assert_eq!(*item, (i + 1) as u64);
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+}
+
+#[test]
+fn test_for_each_leaf_mut() {
+    let seed: u64 = random();
+    println!("test_for_each_leaf_mut seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut vals: Vec<u64> = (0..100_000).map(|_| rng.gen::<u32>() as u64).collect();
+    let mut arr = Vector::from_slice(&vals, Some(128));
+
+    arr.for_each_leaf_mut(|xs| xs.iter_mut().for_each(|x| *x *= 2))
+        .unwrap();
+    vals.iter_mut().for_each(|x| *x *= 2);
+    validate(&arr, &vals);
+
+    // after copy-on-write insert, untouched nodes are shared with the clone.
+    let mut arr1 = arr.clone();
+    arr1.insert(0, 0).unwrap();
+    assert!(arr1
+        .for_each_leaf_mut(|xs| xs.iter_mut().for_each(|x| *x = 0))
+        .is_err());
+    assert!(arr
+        .for_each_leaf_mut(|xs| xs.iter_mut().for_each(|x| *x = 0))
+        .is_err());
+    validate(&arr, &vals);
+
+    mem::drop(arr1);
+    arr.for_each_leaf_mut(|xs| xs.iter_mut().for_each(|x| *x += 1))
+        .unwrap();
+    vals.iter_mut().for_each(|x| *x += 1);
+    validate(&arr, &vals);
+}
+
+#[test]
+fn test_leaf_vecs() {
+    let seed: u64 = random();
+    println!("test_leaf_vecs seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&vals, Some(128));
+        for _ in 0..(*n / 10) {
+            let off = rng.gen::<usize>() % arr.len();
+            arr.update(off, vals[off]).unwrap();
+        }
+
+        // shared leaf nodes.
+        let leafs = arr.clone().into_leaf_vecs();
+        assert!(leafs.iter().all(|data| !data.is_empty()));
+        assert_eq!(leafs.concat(), vals);
+        validate(&Vector::from_leaf_vecs(leafs, Some(128)), &vals);
+
+        // uniquely owned leaf nodes.
+        let leafs = arr.into_leaf_vecs();
+        validate(&Vector::from_leaf_vecs(leafs, Some(128)), &vals);
+    }
+}
+
+#[test]
+fn test_merkle_root() {
+    let seed: u64 = random();
+    println!("test_merkle_root seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let vals: Vec<u64> = (0..100_000).map(|_| rng.gen()).collect();
+    let a = Vector::from_slice(&vals, Some(128));
+    let mut b = Vector::from_slice(&vals, Some(128));
+    assert_eq!(a.merkle_root(), b.merkle_root());
+    assert_eq!(a.merkle_root(), a.clone().merkle_root());
+
+    let off = rng.gen::<usize>() % b.len();
+    let val = b.get(off).unwrap().wrapping_add(1);
+    b.update(off, val).unwrap();
+    assert_ne!(a.merkle_root(), b.merkle_root());
+
+    let c = Vector::from_slice(&vals, Some(256));
+    assert_ne!(a.merkle_root(), c.merkle_root());
+    let (x, y) = (Vector::<u64>::default(), Vector::<u64>::default());
+    assert_eq!(x.merkle_root(), y.merkle_root());
+}
+
+#[test]
+fn test_merkle_root_cached() {
+    let seed: u64 = random();
+    println!("test_merkle_root_cached seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let vals: Vec<u64> = (0..100_000).map(|_| rng.gen()).collect();
+    let mut arr = Vector::from_slice(&vals, Some(128));
+
+    let mut cache = MerkleCache::default();
+    assert_eq!(arr.merkle_root_cached(&mut cache), arr.merkle_root());
+    let n = cache.len();
+    assert_eq!(arr.merkle_root_cached(&mut cache), arr.merkle_root());
+    assert_eq!(cache.len(), n);
+
+    for _ in 0..100 {
+        let off = rng.gen::<usize>() % arr.len();
+        arr.update(off, rng.gen()).unwrap();
+        assert_eq!(arr.merkle_root_cached(&mut cache), arr.merkle_root());
+    }
+    // only the copied path is hashed again for every update.
+    assert!(cache.len() <= n + (100 * 64), "{} {}", cache.len(), n);
+
+    // old versions are not kept alive by the cache.
+    let old = arr.clone();
+    arr.update(0, 0).unwrap();
+    assert_eq!(old.merkle_root_cached(&mut cache), old.merkle_root());
+    assert_eq!(arr.merkle_root_cached(&mut cache), arr.merkle_root());
+    let n = cache.len();
+    mem::drop(old);
+    cache.gc();
+    assert!(cache.len() < n, "{} {}", cache.len(), n);
+    assert_eq!(arr.merkle_root_cached(&mut cache), arr.merkle_root());
+
+    cache.clear();
+    assert!(cache.is_empty());
+    arr.update_mut(0, 0).unwrap();
+}
+
+#[test]
+fn test_serialize_structured() {
+    let seed: u64 = random();
+    println!("test_serialize_structured seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let vals: Vec<u64> = (0..10_000).map(|_| rng.gen()).collect();
+    let one = Vector::from_slice(&vals, Some(128));
+    let mut two = one.clone();
+    for _ in 0..10 {
+        let off = rng.gen::<usize>() % two.len();
+        two.update(off, rng.gen()).unwrap();
+    }
+
+    let mut interner = LeafInterner::default();
+    let mut versions = vec![];
+    for arr in [one, two].iter() {
+        let mut buf = vec![];
+        arr.serialize_structured(&mut serde_json::Serializer::new(&mut buf))
+            .unwrap();
+        let mut de = serde_json::Deserializer::from_slice(&buf);
+        let val = Vector::deserialize_structured(&mut de, &mut interner).unwrap();
+        assert!(val == *arr);
+        assert_eq!(val.merkle_root(), arr.merkle_root());
+        versions.push(val);
+    }
+
+    // leaf nodes not touched by update are shared across versions.
+    mem::drop(interner);
+    let (shared, total) = versions[1].fetch_multiversions();
+    println!(
+        "test_serialize_structured shared:{}/{}",
+        shared.len(),
+        total
+    );
+    assert!(!shared.is_empty());
+}
+
+#[test]
+fn test_deserialize_structured_invalid() {
+    let vals: Vec<u64> = (0..1000).collect();
+    let arr = Vector::from_slice(&vals, Some(128));
+    let val = arr
+        .serialize_structured(serde_json::value::Serializer)
+        .unwrap();
+
+    let mut interner = LeafInterner::default();
+    let res = Vector::<u64>::deserialize_structured(val.clone(), &mut interner);
+    assert!(res.unwrap() == arr);
+
+    // leaf_cap less than item size.
+    for leaf_cap in [0, 7].iter() {
+        let mut val = val.clone();
+        val["leaf_cap"] = serde_json::json!(leaf_cap);
+        let res = Vector::<u64>::deserialize_structured(val, &mut interner);
+        let err = res.unwrap_err().to_string();
+        assert!(err.contains("invalid leaf_cap"), "{}", err);
+    }
+
+    // leaf nodes holding more than leaf_cap.
+    let mut val = val.clone();
+    val["leaf_cap"] = serde_json::json!(64);
+    let res = Vector::<u64>::deserialize_structured(val, &mut interner);
+    let err = res.unwrap_err().to_string();
+    assert!(err.contains("leaf node with 16 items > 8"), "{}", err);
+}
+
+#[test]
+fn test_deserialize_structured_mut() {
+    let vals: Vec<u64> = (0..1000).collect();
+    let arr = Vector::from_slice(&vals, Some(128));
+    let val = arr
+        .serialize_structured(serde_json::value::Serializer)
+        .unwrap();
+    let n_leafs = arr.iter_leaves().count();
+
+    let mut interner = LeafInterner::default();
+    let one = Vector::<u64>::deserialize_structured(val.clone(), &mut interner).unwrap();
+    let mut two = Vector::<u64>::deserialize_structured(val, &mut interner).unwrap();
+    assert_eq!(interner.len(), n_leafs);
+    assert_eq!(two.fetch_multiversions().0.len(), n_leafs);
+
+    // interner does not keep dropped versions alive.
+    mem::drop(one);
+    interner.gc();
+    assert_eq!(interner.len(), n_leafs);
+    mem::drop(arr);
+    interner.gc();
+    assert_eq!(interner.len(), n_leafs);
+
+    // in-place mutation once the interner is cleared.
+    interner.clear();
+    assert!(interner.is_empty());
+    let mut refv = vals.clone();
+    for off in [0, 500, 1000].iter() {
+        two.insert_mut(*off, 0).unwrap();
+        refv.insert(*off, 0);
+    }
+    validate(&two, &refv);
+
+    mem::drop(two);
+    let mut interner = LeafInterner::<u64>::default();
+    interner.intern(vec![1, 2, 3]);
+    assert_eq!(interner.len(), 1);
+    interner.gc();
+    assert!(interner.is_empty());
+}
+
+#[test]
+fn test_verify() {
+    let vals: Vec<u64> = (0..10_000).collect();
+    let mut arr = Vector::from_slice(&vals, Some(128));
+    arr.verify().unwrap();
+
+    arr.len += 1;
+    assert!(arr.verify().is_err());
+    arr.len -= 1;
+
+    let root = match arr.root.as_ref() {
+        Node::M {
+            left,
+            right,
+            weight,
+        } => Node::newm(Ref::clone(left), Ref::clone(right), weight + 1),
+        Node::Z { .. } => unreachable!(),
+    };
+    arr.root = root;
+    assert!(arr.verify().is_err());
+}
+
+#[test]
+fn test_rebalance_iter_order() {
+    let seed: u64 = random();
+    println!("test_rebalance_iter_order seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for leaf_size in [8, 128, 1024].iter() {
+        let mut arr = Vector::default();
+        arr.set_leaf_size(*leaf_size).set_auto_rebalance(false);
+
+        for _ in 0..10_000 {
+            match rng.gen::<u8>() % 4 {
+                0 if !arr.is_empty() => {
+                    let off = rng.gen::<usize>() % arr.len();
+                    arr.remove(off).unwrap();
+                }
+                _ => {
+                    let off = rng.gen::<usize>() % (arr.len() + 1);
+                    arr.insert(off, rng.gen::<u64>()).unwrap();
+                }
+            }
+        }
+
+        let snapshot: Vec<u64> = arr.iter().cloned().collect();
+        for packed in [true, false].iter() {
+            let rebalanced = arr.rebalance(*packed).unwrap();
+            let items: Vec<u64> = rebalanced.iter().cloned().collect();
+            assert_eq!(items, snapshot, "packed:{}", packed);
+
+            let items: Vec<u64> = rebalanced.into_iter().collect();
+            assert_eq!(items, snapshot, "packed:{}", packed);
+        }
+    }
+}
+
+#[test]
+fn test_capacity_reserve() {
+    let vals: Vec<u64> = (0..1000).collect();
+    let mut arr = Vector::from_slice(&vals, Some(1024));
+    // from_slice leaves slack in the rightmost, partially filled, leaf.
+    assert_eq!(arr.capacity(), 1024);
+
+    // rightmost leaf holds 1000 % 128 items.
+    arr.reserve(10);
+    assert!(arr.capacity() >= 1010, "{}", arr.capacity());
+    arr.reserve(1000);
+    assert_eq!(arr.capacity(), 1024);
+
+    let capacity = arr.capacity();
+    for i in 0..24 {
+        arr.insert_mut(arr.len(), i).unwrap();
+    }
+    assert_eq!(arr.capacity(), capacity);
+
+    let shared = arr.clone();
+    arr.reserve(1000);
+    assert_eq!(arr.capacity(), capacity);
+    assert_eq!(shared.capacity(), capacity);
+}
+
+#[test]
+fn test_repack() {
+    let seed: u64 = random();
+    println!("test_repack seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let vals: Vec<u64> = (0..100_000).map(|_| rng.gen()).collect();
+    let arr = Vector::from_slice(&vals, None);
+
+    for leaf_size in [64, 1024, 100_000].iter() {
+        let arr = arr.repack(*leaf_size);
+        assert_eq!(arr.leaf_cap, *leaf_size);
+        validate(&arr, &vals);
+
+        let leafs = arr.clone().into_leaf_vecs();
+        let n = max_leaf_items::<u64>(*leaf_size);
+        assert!(leafs.iter().rev().skip(1).all(|data| data.len() == n));
+
+        let mut arr = arr;
+        arr.insert(0, 0).unwrap();
+        assert_eq!(arr.len(), vals.len() + 1);
+    }
+}
+
+#[test]
+fn test_eq_by() {
+    let a: Vec<char> = "Hello World".chars().collect();
+    let b: Vec<char> = "hELLO wORLD".chars().collect();
+    let c: Vec<char> = "hELLO wORLD!".chars().collect();
+    let (a, b, c) = (
+        Vector::from_slice(&a, Some(16)),
+        Vector::from_slice(&b, Some(16)),
+        Vector::from_slice(&c, Some(16)),
+    );
+
+    assert!(a != b);
+    assert!(a.eq_by(&b, |x, y| x.eq_ignore_ascii_case(y)));
+    assert!(!a.eq_by(&c, |x, y| x.eq_ignore_ascii_case(y)));
+
+    let d = a.clone();
+    assert!(a.eq_by(&d, |_, _| panic!("shared root must not compare items")));
+}
+
+#[test]
+fn test_extend_front_from_slice() {
+    let seed: u64 = random();
+    println!("test_extend_front_from_slice seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut arr = Vector::default();
+    arr.set_leaf_size(1024);
+    let mut refv: Vec<u64> = vec![];
+
+    for _ in 0..100 {
+        let mut items: Vec<u64> = (0..(rng.gen::<usize>() % 1000))
+            .map(|_| rng.gen())
+            .collect();
+        arr.extend_front_from_slice(&items);
+        items.extend_from_slice(&refv);
+        refv = items;
+    }
+    validate(&arr, &refv);
+    validate(&arr.rebalance(false).unwrap(), &refv);
+}
+
+#[test]
+fn test_rchunks() {
+    let seed: u64 = random();
+    println!("test_rchunks seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        for size in [1, 3, 16, 100, 20_000].iter() {
+            let chunks: Vec<Vector<u64>> = arr.rchunks(*size).unwrap().collect();
+            let refs: Vec<&[u64]> = vals.rchunks(*size).collect();
+            assert_eq!(chunks.len(), refs.len());
+            for (chunk, refv) in chunks.iter().zip(refs) {
+                assert!(chunk.iter().eq(refv.iter()));
+            }
+        }
+
+        assert!(arr.rchunks(0).is_err());
+    }
+}
+
+#[test]
+fn test_iter_leaves() {
+    let seed: u64 = random();
+    println!("test_iter_leaves seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+        for _ in 0..(*n / 10) {
+            let off = rng.gen::<usize>() % (arr.len() + 1);
+            arr.insert(off, off as u64).unwrap();
+            refv.insert(off, off as u64);
+        }
+
+        let mut n = 0;
+        for (off, data) in arr.iter_leaves() {
+            assert_eq!(off, n);
+            assert!(!data.is_empty());
+            assert_eq!(data, &refv[off..(off + data.len())]);
+            n += data.len();
+        }
+        assert_eq!(n, refv.len());
+    }
+}
+
+#[test]
+fn test_try_reduce_tree() {
+    let seed: u64 = random();
+    println!("test_try_reduce_tree seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let leaf = |xs: &[u32]| xs.iter().map(|x| *x as u64).sum::<u64>();
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u32> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        let sum = arr.try_reduce_tree(leaf, |a, b| a.checked_add(b).ok_or("overflow"));
+        let refv = vals.iter().map(|x| *x as u64).reduce(|a, b| a + b);
+        assert_eq!(sum, Ok(refv));
+
+        let calls = std::cell::Cell::new(0);
+        let res = arr.try_reduce_tree(leaf, |_, _| {
+            calls.set(calls.get() + 1);
+            Err::<u64, &str>("fail")
+        });
+        match arr.iter_leaves().count() {
+            0 | 1 => assert_eq!(calls.get(), 0),
+            _ => {
+                assert_eq!(res, Err("fail"));
+                assert_eq!(calls.get(), 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_dedup_by_key() {
+    let seed: u64 = random();
+    println!("test_dedup_by_key seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // 8 items per leaf, equal keys straddling the seam between two leafs.
+    let mut vals: Vec<(u64, u64)> = (0..16).map(|i| (i, 0)).collect();
+    vals[8] = (7, 1);
+    let mut arr = Vector::from_slice(&vals, Some(128));
+    assert_eq!(
+        arr.iter_leaves()
+            .map(|(_, d)| d.len())
+            .collect::<Vec<usize>>(),
+        vec![8, 8]
+    );
+
+    arr.dedup_by_key(|x| x.0);
+    vals.dedup_by_key(|x| x.0);
+    assert_eq!(arr.get(7).unwrap(), &(7, 0));
+    validate(&arr, &vals);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut vals: Vec<(u64, u64)> =
+            (0..*n).map(|_| (rng.gen::<u64>() % 4, rng.gen())).collect();
+        let mut arr = Vector::from_slice(&vals, Some(128));
+        arr.dedup_by_key(|x| x.0);
+        vals.dedup_by_key(|x| x.0);
+        validate(&arr, &vals);
+    }
+}
+
+#[test]
+fn test_merge_by() {
+    let seed: u64 = random();
+    println!("test_merge_by seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..10 {
+        let (n, m) = (rng.gen::<usize>() % 10_000, rng.gen::<usize>() % 10_000);
+        // (key, source) pairs, sorted by key.
+        let mut xs: Vec<(u8, u8)> = (0..n).map(|_| (rng.gen(), 0)).collect();
+        let mut ys: Vec<(u8, u8)> = (0..m).map(|_| (rng.gen(), 1)).collect();
+        xs.sort_unstable();
+        ys.sort_unstable();
+
+        let a = Vector::from_slice(&xs, Some(128));
+        let b = Vector::from_slice(&ys, Some(128));
+        let arr = a.merge_by(&b, |x, y| x.0.cmp(&y.0));
+
+        let mut refv = xs.clone();
+        refv.extend_from_slice(&ys);
+        refv.sort_by_key(|x| x.0); // stable sort
+        validate(&arr, &refv);
+    }
+}
+
+#[test]
+fn test_node_pool() {
+    let seed: u64 = random();
+    println!("test_node_pool seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut refv: Vec<u64> = (0..10_000).map(|_| rng.gen()).collect();
+    let mut arr = Vector::from_slice(&refv, Some(128));
+
+    for _ in 0..1000 {
+        let off = rng.gen::<usize>() % arr.len();
+        assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+        let val = rng.gen::<u64>();
+        arr.insert(off, val).unwrap();
+        refv.insert(off, val);
+    }
+    // nodes are recycled only with the `pool` feature.
+    #[cfg(feature = "pool")]
+    assert!(!arr.pool.nodes.is_empty());
+    #[cfg(not(feature = "pool"))]
+    assert_eq!(mem::size_of_val(&arr.pool), 0);
+    validate(&arr, &refv);
+
+    // older versions sharing nodes with arr shall remain untouched.
+    let mut versions = vec![];
+    for _ in 0..1000 {
+        versions.push((arr.clone(), refv.clone()));
+        let off = rng.gen::<usize>() % arr.len();
+        assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+        let val = rng.gen::<u64>();
+        arr.insert(off, val).unwrap();
+        refv.insert(off, val);
+    }
+    validate(&arr, &refv);
+    for (arr, refv) in versions.iter().step_by(100) {
+        validate(arr, refv);
+    }
+}
+
+#[cfg(not(feature = "pool"))]
+#[test]
+fn test_vector_size() {
+    // len, root, leaf_cap, on_rebalance (fat pointer) and the flags.
+    let word = mem::size_of::<usize>();
+    assert_eq!(mem::size_of::<Vector<u64>>(), 6 * word);
+    assert_eq!(mem::size_of::<Vector<Vector<u64>>>(), 6 * word);
+}
+
+#[test]
+fn test_par_from_slice() {
+    let seed: u64 = random();
+    println!("test_par_from_slice seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        for leaf_size in [128, 1024, crate::LEAF_CAP].iter() {
+            let arr = Vector::par_from_slice(&refv, Some(*leaf_size));
+            let seq = Vector::from_slice(&refv, Some(*leaf_size));
+            assert_eq!(arr.merkle_root(), seq.merkle_root());
+            validate(&arr, &refv);
+        }
+    }
+}
+
+#[test]
+fn test_chunks_exact() {
+    let seed: u64 = random();
+    println!("test_chunks_exact seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        for size in [1, 3, 16, 100, 20_000].iter() {
+            let iter = arr.chunks_exact(*size).unwrap();
+            let refs = vals.chunks_exact(*size);
+            assert_eq!(iter.size_hint(), refs.size_hint());
+            assert!(iter.remainder().iter().eq(refs.remainder().iter()));
+
+            let chunks: Vec<Vector<u64>> = iter.collect();
+            let refs: Vec<&[u64]> = refs.collect();
+            assert_eq!(chunks.len(), refs.len());
+            for (chunk, refv) in chunks.iter().zip(refs) {
+                validate(chunk, refv);
+            }
+        }
+
+        assert!(arr.chunks_exact(0).is_err());
+    }
+}
+
+#[test]
+fn test_split_at_checked() {
+    let seed: u64 = random();
+    println!("test_split_at_checked seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        for mid in [0, *n / 2, *n].iter() {
+            let (left, right) = arr.split_at_checked(*mid).unwrap();
+            let (lrefv, rrefv) = vals.split_at(*mid);
+            validate(&left, lrefv);
+            validate(&right, rrefv);
+        }
+        assert!(arr.split_at_checked(*n + 1).is_none());
+        validate(&arr, &vals);
+    }
+}
+
+#[test]
+fn test_trim_matches() {
+    let seed: u64 = random();
+    println!("test_trim_matches seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        for _ in 0..10 {
+            let mut vals: Vec<u8> = (0..*n).map(|_| rng.gen::<u8>() % 2).collect();
+            let (start, end) = match *n {
+                0 => (0, 0),
+                n => (rng.gen::<usize>() % n, rng.gen::<usize>() % n),
+            };
+            vals[..start].iter_mut().for_each(|x| *x = 0);
+            vals[end..].iter_mut().for_each(|x| *x = 0);
+            let arr = Vector::from_slice(&vals, Some(128));
+
+            let refv: Vec<u8> = vals.iter().copied().skip_while(|x| *x == 0).collect();
+            validate(&arr.trim_start_matches(|x| *x == 0), &refv);
+
+            let k = vals.iter().rev().take_while(|x| **x == 0).count();
+            validate(&arr.trim_end_matches(|x| *x == 0), &vals[..(*n - k)]);
+        }
+    }
+
+    let vals: Vec<u8> = vec![1, 2, 3];
+    let arr = Vector::from_slice(&vals, Some(128));
+    validate(&arr.trim_start_matches(|_| true), &[]);
+    validate(&arr.trim_end_matches(|_| true), &[]);
+    validate(&arr.trim_end_matches(|_| false), &vals);
+}
+
+#[test]
+fn test_get_wrapping() {
+    let seed: u64 = random();
+    println!("test_get_wrapping seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let arr: Vector<u64> = Vector::default();
+    assert!(arr.get_wrapping(0).is_none());
+    assert!(arr.get_wrapping(-1).is_none());
+
+    for n in [1, 10, 1000, 10_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        let n = *n as isize;
+        assert_eq!(arr.get_wrapping(-1), vals.last());
+        assert_eq!(arr.get_wrapping(n), vals.first());
+        assert_eq!(
+            arr.get_wrapping(isize::MIN),
+            Some(&vals[isize::MIN.rem_euclid(n) as usize])
+        );
+        assert_eq!(
+            arr.get_wrapping(isize::MAX),
+            Some(&vals[isize::MAX.rem_euclid(n) as usize])
+        );
+        for _ in 0..1000 {
+            let index = rng.gen::<isize>() % (n * 4);
+            let off = index.rem_euclid(n) as usize;
+            assert_eq!(arr.get_wrapping(index), Some(&vals[off]));
+        }
+    }
+}
+
+#[test]
+fn test_is_sorted() {
+    let seed: u64 = random();
+    println!("test_is_sorted seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 2, 10, 1000, 10_000].iter() {
+        let mut vals: Vec<u64> = (0..*n).map(|_| (rng.gen::<u64>() % 1000) + 1).collect();
+        vals.sort_unstable();
+        let arr = Vector::from_slice(&vals, Some(128));
+        assert!(arr.is_sorted());
+        assert!(arr.is_sorted_by(|a, b| a <= b));
+        assert!(arr.is_sorted_by_key(|x| x / 10));
+
+        // break the order at every leaf seam.
+        let mut off = 0;
+        for (_, leaf) in arr.iter_leaves() {
+            off += leaf.len();
+            if off < vals.len() {
+                let mut refv = vals.clone();
+                refv[off] = refv[off - 1] - 1;
+                let arr = Vector::from_slice(&refv, Some(128));
+                assert!(!arr.is_sorted(), "{} {:?}", off, &refv[(off - 1)..=off]);
+                assert!(!arr.is_sorted_by_key(|x| *x));
+            }
+        }
+    }
+
+    let arr = Vector::from_slice(&[1.0, f64::NAN, 2.0], None);
+    assert!(!arr.is_sorted());
+}
+
+#[test]
+fn test_find() {
+    let seed: u64 = random();
+    println!("test_find seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>() % 1000).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        for _ in 0..100 {
+            let key = rng.gen::<u64>() % 1000;
+            let refv = vals.iter().enumerate().find(|(_, x)| **x == key);
+            assert_eq!(arr.find(|x| *x == key), refv);
+        }
+        assert_eq!(arr.find(|_| false), None);
+    }
+}
+
+#[test]
+fn test_clone_range() {
+    let seed: u64 = random();
+    println!("test_clone_range seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        for _ in 0..10 {
+            let (a, b) = (rng.gen::<usize>() % (n + 1), rng.gen::<usize>() % (n + 1));
+            let (start, end) = (a.min(b), a.max(b));
+
+            let mut val = arr.clone_range(start..end).unwrap();
+            let mut refv = vals[start..end].to_vec();
+            validate(&val, &refv);
+
+            // in-place mutation shall not panic, nor touch the parent.
+            if !refv.is_empty() {
+                let off = rng.gen::<usize>() % refv.len();
+                val.update_mut(off, 0).unwrap();
+                refv[off] = 0;
+                val.insert_mut(off, 1).unwrap();
+                refv.insert(off, 1);
+                validate(&val, &refv);
+            }
+        }
+        validate(&arr, &vals);
+
+        validate(&arr.clone_range(..).unwrap(), &vals);
+        assert!(arr.clone_range(..(n + 1)).is_err());
+    }
+}
+
+#[test]
+fn test_position_max_min() {
+    let seed: u64 = random();
+    println!("test_position_max_min seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let vals: Vec<f64> = (0..*n).map(|_| (rng.gen::<u64>() % 100) as f64).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        let cmp = |a: &f64, b: &f64| a.partial_cmp(b).unwrap();
+        let refv = vals.iter().enumerate().max_by(|a, b| cmp(a.1, b.1));
+        assert_eq!(arr.position_max_by(cmp), refv.map(|x| x.0));
+        let refv = vals.iter().enumerate().min_by(|a, b| cmp(a.1, b.1));
+        assert_eq!(arr.position_min_by(cmp), refv.map(|x| x.0));
+
+        let key = |x: &f64| (*x as u64) % 7;
+        let refv = vals.iter().enumerate().max_by_key(|x| key(x.1));
+        assert_eq!(arr.position_max_by_key(key), refv.map(|x| x.0));
+        let refv = vals.iter().enumerate().min_by_key(|x| key(x.1));
+        assert_eq!(arr.position_min_by_key(key), refv.map(|x| x.0));
+
+        if let Some(off) = arr.position_max_by(cmp) {
+            assert_eq!(arr.get(off).unwrap(), arr.max_by(cmp).unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_swap_ranges() {
+    let seed: u64 = random();
+    println!("test_swap_ranges seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(1024));
+
+        for _ in 0..100 {
+            let k = rng.gen::<usize>() % (n / 2 + 1);
+            let a = rng.gen::<usize>() % (n - (2 * k) + 1);
+            let b = a + k + (rng.gen::<usize>() % (n - a - (2 * k) + 1));
+            let (a, b) = match rng.gen::<bool>() {
+                true => (a..a + k, b..b + k),
+                false => (b..b + k, a..a + k),
+            };
+
+            arr.swap_ranges(a.clone(), b.clone()).unwrap();
+            let (x, y) = if a.start < b.start { (a, b) } else { (b, a) };
+            let (head, rest) = refv.split_at_mut(y.start);
+            head[x].swap_with_slice(&mut rest[..y.end - y.start]);
+        }
+        arr = arr.rebalance(true).unwrap();
+        validate(&arr, &refv);
+
+        if *n >= 10 {
+            assert!(arr.swap_ranges(0..2, 1..3).is_err());
+            assert!(arr.swap_ranges(0..2, 4..7).is_err());
+            assert!(arr.swap_ranges(0..2, (n - 1)..(n + 1)).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_fold_leaves() {
+    let seed: u64 = random();
+    println!("test_fold_leaves seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u8> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        let hist = arr.fold_leaves([0_usize; 256], |mut hist, data| {
+            data.iter().for_each(|x| hist[*x as usize] += 1);
+            hist
+        });
+        let mut refv = [0_usize; 256];
+        vals.iter().for_each(|x| refv[*x as usize] += 1);
+        assert_eq!(hist, refv);
+
+        let n_leafs = arr.fold_leaves(0, |acc, _| acc + 1);
+        assert_eq!(n_leafs, arr.iter_leaves().count());
+    }
+}
+
+#[test]
+fn test_extract_if() {
+    let seed: u64 = random();
+    println!("test_extract_if seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&vals, Some(128));
+        arr.set_auto_rebalance(false);
+
+        let m = (rng.gen::<u64>() % 4) + 1;
+        let val = arr.extract_if(|x| x % m == 0);
+        let (refx, refv): (Vec<u64>, Vec<u64>) = vals.iter().partition(|x| *x % m == 0);
+        validate(&val, &refx);
+        validate(&arr, &refv);
+        assert!(!arr.auto_rebalance && !val.auto_rebalance);
+    }
+}
+
+#[test]
+fn test_get_disjoint_mut() {
+    let seed: u64 = random();
+    println!("test_get_disjoint_mut seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [2, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+
+        for _ in 0..1000 {
+            let (i, j) = (rng.gen::<usize>() % n, rng.gen::<usize>() % n);
+            if i == j {
+                assert!(arr.get_disjoint_mut(i, j).is_err());
+                continue;
+            }
+            let (a, b) = arr.get_disjoint_mut(i, j).unwrap();
+            assert_eq!((*a, *b), (refv[i], refv[j]));
+            std::mem::swap(a, b);
+            refv.swap(i, j);
+        }
+        validate(&arr, &refv);
+
+        assert!(arr.get_disjoint_mut(0, *n).is_err());
+        assert!(arr.get_disjoint_mut(*n, 0).is_err());
+
+        let shared = arr.clone();
+        match arr.get_disjoint_mut(0, n - 1) {
+            Err(Error::Shared(_, _)) => (),
+            _ => panic!("expected shared error"),
+        }
+        validate(&shared, &refv);
+    }
+}
+
+#[test]
+fn test_to_from_bytes() {
+    let seed: u64 = random();
+    println!("test_to_from_bytes seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u32> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        let bytes = arr.to_bytes();
+        assert_eq!(bytes.as_slice(), bytemuck::cast_slice::<u32, u8>(&vals));
+        validate(
+            &Vector::<u32>::from_bytes(&bytes, Some(128)).unwrap(),
+            &vals,
+        );
+
+        // unaligned input
+        let mut buf = vec![0_u8];
+        buf.extend_from_slice(&bytes);
+        validate(&Vector::<u32>::from_bytes(&buf[1..], None).unwrap(), &vals);
+        assert!(Vector::<u32>::from_bytes(&buf, None).is_err());
+    }
+}
+
+#[test]
+fn test_get_with_remaining() {
+    let seed: u64 = random();
+    println!("test_get_with_remaining seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        for (off, val) in vals.iter().enumerate() {
+            assert_eq!(arr.get_with_remaining(off), Some((val, n - off - 1)));
+        }
+        assert_eq!(arr.get_with_remaining(*n), None);
+        assert_eq!(arr.get_with_remaining(usize::MAX), None);
+    }
+}
+
+#[test]
+fn test_extend() {
+    let seed: u64 = random();
+    println!("test_extend seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut refv: Vec<u64> = vec![];
+    let mut arr: Vector<u64> = Vector::new().with_leaf_size(128);
+    for _ in 0..100 {
+        let vals: Vec<u64> = (0..rng.gen::<usize>() % 1000).map(|_| rng.gen()).collect();
+        match rng.gen::<bool>() {
+            true => arr.extend(vals.iter()),
+            false => arr.extend(vals.clone()),
+        }
+        refv.extend(vals.iter());
+        assert_eq!(arr.leaf_cap, 128);
+    }
+    validate(&arr, &refv);
+}
+
+#[test]
+fn test_on_rebalance() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        Arc,
+    };
+
+    let seed: u64 = random();
+    println!("test_on_rebalance seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let (count, leafs) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let (c, l) = (Arc::clone(&count), Arc::clone(&leafs));
+
+    let mut refv: Vec<u64> = vec![];
+    let mut arr: Vector<u64> = Vector::new();
+    arr.set_on_rebalance(Some(Box::new(move |evnt: RebalanceEvent| {
+        assert!(evnt.depth >= crate::REBALANCE_THRESHOLD);
+        assert!(!evnt.packed);
+        c.fetch_add(1, SeqCst);
+        l.store(evnt.n_leafs, SeqCst);
+    })));
+    for _ in 0..100_000 {
+        let val = rng.gen::<u64>();
+        arr.insert(0, val).unwrap();
+        refv.insert(0, val);
+    }
+    validate(&arr, &refv);
+    assert!(count.load(SeqCst) > 0);
+    assert!(leafs.load(SeqCst) > 0);
+
+    let n = count.load(SeqCst);
+    let mut arr = arr.clone();
+    arr.set_on_rebalance(None);
+    for _ in 0..100_000 {
+        arr.insert(0, rng.gen::<u64>()).unwrap();
+    }
+    assert_eq!(count.load(SeqCst), n);
+
+    let c = Arc::clone(&count);
+    arr.set_on_rebalance(Some(Box::new(move |evnt: RebalanceEvent| {
+        assert_eq!(evnt.depth, 0);
+        assert!(evnt.packed);
+        c.fetch_add(1, SeqCst);
+    })));
+    arr.rebalance(true).unwrap();
+    assert_eq!(count.load(SeqCst), n + 1);
+}
+
+#[test]
+fn test_debug_checks() {
+    let seed: u64 = random();
+    println!("test_debug_checks seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut refv: Vec<u64> = (0..10_000).map(|_| rng.gen()).collect();
+    let mut arr = Vector::from_slice(&refv, Some(1024));
+    arr.set_debug_checks(true);
+    for _ in 0..1000 {
+        let off = rng.gen::<usize>() % arr.len();
+        let val = rng.gen::<u64>();
+        match rng.gen::<u8>() % 4 {
+            0 => {
+                arr.insert(off, val).unwrap();
+                refv.insert(off, val);
+            }
+            1 => assert_eq!(arr.remove(off).unwrap(), refv.remove(off)),
+            2 => {
+                let tail = arr.split_off(off).unwrap();
+                assert!(tail.debug_checks);
+                arr.append(tail);
+            }
+            _ => {
+                arr.insert_mut(off, val).unwrap();
+                refv.insert(off, val);
+            }
+        }
+    }
+    let arr = arr.rebalance(true).unwrap();
+    assert!(arr.debug_checks);
+    validate(&arr, &refv);
+
+    // corrupt the length of the vector.
+    let mut arr = Vector::from_slice(&refv, Some(128));
+    arr.set_debug_checks(true);
+    arr.len += 1;
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        arr.remove(0).ok();
+    }));
+    let err = res.unwrap_err();
+    let msg = err.downcast_ref::<String>().unwrap();
+    assert!(msg.contains("after remove"), "{}", msg);
+}
+
+#[test]
+fn test_leaf_ranges() {
+    let seed: u64 = random();
+    println!("test_leaf_ranges seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        let ranges = arr.leaf_ranges();
+        assert_eq!(ranges, arr.iter_leaves().collect::<Vec<(usize, &[u64])>>());
+        for (off, data) in ranges.iter() {
+            assert_eq!(*data, &vals[*off..(*off + data.len())]);
+        }
+        let total: usize = ranges.iter().map(|(_, data)| data.len()).sum();
+        assert_eq!(total, *n);
+    }
+}
+
+#[test]
+fn test_update_if_unchanged() {
+    let seed: u64 = random();
+    println!("test_update_if_unchanged seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let vals: Vec<u64> = (0..10_000).map(|_| rng.gen()).collect();
+    let arr = Vector::from_slice(&vals, Some(128));
+    let base = arr.clone();
+    assert_eq!(arr.root_ptr(), base.root_ptr());
+
+    let update = |arr: &Vector<u64>| {
+        let mut arr = arr.clone();
+        arr.update(0, 0).unwrap();
+        arr
+    };
+    let new = arr.update_if_unchanged(base.root_ptr(), update).unwrap();
+    assert_ne!(new.root_ptr(), base.root_ptr());
+    assert_eq!(new.get(0).unwrap(), &0);
+    assert!(new.update_if_unchanged(base.root_ptr(), update).is_none());
+    validate(&arr, &vals);
+}
+
+#[test]
+fn test_iter_at() {
+    let seed: u64 = random();
+    println!("test_iter_at seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+        // mix of empty and partially filled leaf nodes.
+        for _ in 0..(n / 2) {
+            let off = rng.gen::<usize>() % arr.len();
+            arr.remove(off).unwrap();
+            refv.remove(off);
+        }
+
+        let n = refv.len();
+        for index in (0..=n).chain(vec![n + 1, usize::MAX]) {
+            let off = index.min(n);
+            assert!(arr.iter_at(index).eq(refv[off..].iter()), "{}", index);
+        }
+    }
+}
+
+#[test]
+fn test_split_off_nonempty() {
+    let seed: u64 = random();
+    println!("test_split_off_nonempty seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+
+        assert!(arr.split_off_nonempty(0).is_none());
+        assert!(arr.split_off_nonempty(*n).is_none());
+        assert!(arr.split_off_nonempty(n + 1).is_none());
+        validate(&arr, &refv);
+
+        while arr.len() > 1 {
+            let off = (rng.gen::<usize>() % (arr.len() - 1)) + 1;
+            let tail = arr.split_off_nonempty(off).unwrap();
+            validate(&tail, &refv.split_off(off));
+            validate(&arr, &refv);
+        }
+    }
+}
+
+#[test]
+fn test_rebalance_cheap() {
+    let seed: u64 = random();
+    println!("test_rebalance_cheap seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(1024));
+
+        // from_slice builds a balanced and packed tree.
+        for packed in [true, false].iter() {
+            let val = arr.rebalance(*packed).unwrap();
+            assert_eq!(val.root_ptr(), arr.root_ptr());
+            let val = arr.force_rebalance(*packed).unwrap();
+            validate(&val, &refv);
+        }
+
+        for _ in 0..(n / 10) {
+            let off = rng.gen::<usize>() % (arr.len() + 1);
+            let val = rng.gen::<u64>();
+            arr.insert(off, val).unwrap();
+            refv.insert(off, val);
+        }
+        if *n >= 1000 {
+            // leaf nodes are split, tree is no more packed.
+            let val = arr.rebalance(true).unwrap();
+            assert_ne!(val.root_ptr(), arr.root_ptr());
+            arr = val;
+        }
+        let val = arr.rebalance(true).unwrap();
+        assert_eq!(val.root_ptr(), arr.root_ptr());
+        validate(&arr, &refv);
+    }
+}
+
+#[test]
+fn test_split_on() {
+    let seed: u64 = random();
+    println!("test_split_on seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let text: String = (0..*n)
+            .map(|_| match rng.gen::<u8>() % 8 {
+                0 => '\n',
+                x => (b'a' + x) as char,
+            })
+            .collect();
+        let chars: Vec<char> = text.chars().collect();
+        let arr = Vector::from_slice(&chars, Some(128));
+
+        let pieces: Vec<Vector<char>> = arr.split_on(&'\n').collect();
+        let refs: Vec<&str> = text.split('\n').collect();
+        assert_eq!(pieces.len(), refs.len());
+        for (piece, refv) in pieces.iter().zip(refs) {
+            let refv: Vec<char> = refv.chars().collect();
+            validate(piece, &refv);
+        }
+    }
+
+    let arr = Vector::from_slice(&[0_u8, 0, 1, 0], None);
+    let pieces: Vec<Vec<u8>> = arr.split_on(&0).map(Vec::from).collect();
+    assert_eq!(pieces, vec![vec![], vec![], vec![1], vec![]]);
+}
+
+#[test]
+fn test_count_by() {
+    let seed: u64 = random();
+    println!("test_count_by seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u8> = (0..*n).map(|_| rng.gen::<u8>() % 16).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        for x in 0..16 {
+            assert_eq!(arr.count(&x), vals.iter().filter(|y| **y == x).count());
+            let refn = vals.iter().filter(|y| **y > x).count();
+            assert_eq!(arr.count_by(|y| *y > x), refn);
+        }
+    }
+}
+
+#[test]
+fn test_as_contiguous() {
+    let seed: u64 = random();
+    println!("test_as_contiguous seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let arr: Vector<u8> = Vector::new();
+    assert_eq!(arr.as_contiguous(), Some(&[] as &[u8]));
+
+    let vals: Vec<u8> = (0..100).map(|_| rng.gen()).collect();
+    let arr = Vector::from_slice(&vals, None);
+    assert_eq!(arr.as_contiguous(), Some(vals.as_slice()));
+
+    let vals: Vec<u8> = (0..10_000).map(|_| rng.gen()).collect();
+    let arr = Vector::from_slice(&vals, Some(128));
+    assert_eq!(arr.as_contiguous(), None);
+}
+
+#[test]
+fn test_par_for_each_leaf_mut() {
+    let seed: u64 = random();
+    println!("test_par_for_each_leaf_mut seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut vals: Vec<u64> = (0..100_000).map(|_| rng.gen::<u32>() as u64).collect();
+    let mut arr = Vector::from_slice(&vals, Some(128));
+
+    arr.par_for_each_leaf_mut(|xs| xs.iter_mut().for_each(|x| *x *= 2))
+        .unwrap();
+    vals.iter_mut().for_each(|x| *x *= 2);
+    validate(&arr, &vals);
+
+    let mut arr1 = arr.clone();
+    arr1.insert(0, 0).unwrap();
+    assert!(arr1.par_for_each_leaf_mut(|xs| xs.fill(0)).is_err());
+    assert!(arr.par_for_each_leaf_mut(|xs| xs.fill(0)).is_err());
+    validate(&arr, &vals);
+
+    mem::drop(arr1);
+    arr.par_for_each_leaf_mut(|xs| xs.iter_mut().for_each(|x| *x += 1))
+        .unwrap();
+    vals.iter_mut().for_each(|x| *x += 1);
+    validate(&arr, &vals);
+}
+
+#[test]
+fn test_equal_range_by_key() {
+    let seed: u64 = random();
+    println!("test_equal_range_by_key seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut vals: Vec<(u64, u64)> = (0..*n)
+            .map(|_| (rng.gen::<u64>() % 1000, rng.gen()))
+            .collect();
+        vals.sort_by_key(|x| x.0);
+        let mut arr = Vector::from_slice(&vals, Some(128));
+        if *n > 0 {
+            // introduce empty leaf nodes.
+            for _ in 0..(n / 2) {
+                let off = rng.gen::<usize>() % arr.len();
+                arr.remove(off).unwrap();
+                vals.remove(off);
+            }
+        }
+
+        for key in 0..1001 {
+            let lo = vals.partition_point(|x| x.0 < key);
+            let hi = vals.partition_point(|x| x.0 <= key);
+            assert_eq!(arr.equal_range_by_key(&key, |x| x.0), lo..hi, "{}", key);
+        }
+    }
+}
+
+#[test]
+fn test_unique() {
+    let seed: u64 = random();
+    println!("test_unique seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>() % 5000).collect();
+        let arr = Vector::from_slice(&vals, Some(128));
+
+        let mut seen = std::collections::HashSet::new();
+        let refv: Vec<u64> = vals.iter().copied().filter(|x| seen.insert(*x)).collect();
+        validate(&arr.unique(), &refv);
+    }
+}
+
+#[test]
+fn test_memory_report() {
+    let seed: u64 = random();
+    println!("test_memory_report seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut arr = Vector::from_slice(&(0..*n).collect::<Vec<u64>>(), Some(128));
+        for _ in 0..(*n / 10) {
+            let off = rng.gen::<usize>() % arr.len();
+            arr.remove(off).unwrap();
+        }
+
+        let report = arr.memory_report();
+        let (depth, n_leafs) = arr.root.depth_leafs();
+        assert_eq!(report.depth, depth);
+        assert_eq!(report.n_leafs, n_leafs);
+        assert_eq!(report.data_bytes, arr.len() * 8);
+        assert!(report.data_bytes <= report.data_capacity_bytes);
+        assert_eq!(
+            mem::size_of_val(&arr) + report.total(),
+            arr.footprint(),
+            "n:{}",
+            n
+        );
+        if arr.is_empty() {
+            assert_eq!(report.overhead, 0.0);
+        } else {
+            assert!(report.overhead >= 0.0, "{:?}", report);
+        }
+    }
+}
+
+#[test]
+fn test_rebuild_with() {
+    let seed: u64 = random();
+    println!("test_rebuild_with seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut arr = Vector::from_slice(&(0..*n).collect::<Vec<u64>>(), Some(128));
+        arr.set_auto_rebalance(false);
+        let mut refv: Vec<u64> = (0..*n).collect();
+        for _ in 0..(*n / 10) {
+            let off = rng.gen::<usize>() % (arr.len() + 1);
+            arr.insert(off, off as u64).unwrap();
+            refv.insert(off, off as u64);
+        }
+
+        // shared with a clone, elements must be cloned out.
+        let shared = arr.clone();
+        let arr = arr.rebuild_with(|x| x * 2, None);
+        let refv: Vec<u64> = refv.iter().map(|x| x * 2).collect();
+        validate(&arr, &refv);
+        assert!(arr.root.is_packed(max_leaf_items::<u64>(128)));
+        assert!(!arr.auto_rebalance);
+        assert_eq!(shared.len(), arr.len());
+
+        let arr = arr.rebuild_with(|x| x + 1, Some(1024));
+        let refv: Vec<u64> = refv.iter().map(|x| x + 1).collect();
+        validate(&arr, &refv);
+        assert_eq!(arr.leaf_cap, 1024);
+        assert!(arr.root.is_packed(max_leaf_items::<u64>(1024)));
+    }
+}
+
+#[test]
+fn test_get_contiguous_range() {
+    let seed: u64 = random();
+    println!("test_get_contiguous_range seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).collect();
+        let arr = Vector::from_slice(&refv, Some(128));
+        let n = *n as usize;
+
+        assert_eq!(arr.get_contiguous_range(0..0), Some(&[][..]));
+        assert_eq!(arr.get_contiguous_range(0..(n + 1)), None);
+        if n == 1 || n == 10 {
+            assert_eq!(arr.get_contiguous_range(0..n), Some(&refv[..]));
+        }
+
+        let (mut n_some, mut n_none) = (0, 0);
+        for _ in 0..1000 {
+            let start = rng.gen::<usize>() % (n + 1);
+            let end = start + (rng.gen::<usize>() % 20);
+            match arr.get_contiguous_range(start..end) {
+                Some(val) => {
+                    assert_eq!(val, &refv[start..end]);
+                    n_some += 1;
+                }
+                None if end > n => (),
+                None => {
+                    let leafs = arr.leaf_ranges();
+                    let (off, data) =
+                        leafs.iter().rev().find(|(off, _)| *off <= start).unwrap();
+                    assert!(end > off + data.len(), "{}..{}", start, end);
+                    n_none += 1;
+                }
+            }
+        }
+        println!(
+            "test_get_contiguous_range n:{} some:{} none:{}",
+            n, n_some, n_none
+        );
+    }
+}
+
+#[test]
+fn test_concat_iter() {
+    let seed: u64 = random();
+    println!("test_concat_iter seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let arr: Vector<u64> = Vector::concat_iter(vec![]);
+    validate(&arr, &[]);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = vec![];
+        let parts: Vec<Vec<u64>> = (0..(rng.gen::<usize>() % 20))
+            .map(|_| {
+                let m = rng.gen::<usize>() % (*n + 1);
+                let part: Vec<u64> = (0..m).map(|_| rng.gen()).collect();
+                refv.extend_from_slice(&part);
+                part
+            })
+            .collect();
+
+        let iter = parts
+            .iter()
+            .map(|part| Vector::from_slice(part, Some(1024)));
+        let arr = Vector::concat_iter(iter);
+        validate(&arr, &refv);
+        let (depth, n_leafs) = arr.root.depth_leafs();
+        assert!(depth <= (n_leafs as f64).log2().ceil() as usize + 1);
+
+        // parts with a different leaf node size are rebuilt, like append.
+        let leaf_sizes = [128, 1024, crate::LEAF_CAP];
+        let iter = parts.iter().map(|part| {
+            let leaf_size = leaf_sizes[rng.gen::<usize>() % leaf_sizes.len()];
+            Vector::from_slice(part, Some(leaf_size))
+        });
+        let arr = Vector::concat_iter(iter);
+        validate(&arr, &refv);
+        let cap = max_leaf_items::<u64>(arr.leaf_cap);
+        assert!(arr.iter_leaves().all(|(_, data)| data.len() <= cap));
+    }
+}
+
+#[test]
+fn test_trim_to_footprint() {
+    let seed: u64 = random();
+    println!("test_trim_to_footprint seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).collect();
+        let arr = Vector::from_slice(&refv, Some(1024));
+        let footprint = arr.footprint();
+
+        let mut val = arr.clone();
+        val.trim_to_footprint(footprint);
+        validate(&val, &refv);
+
+        let mut val = arr.clone();
+        val.trim_to_footprint(0);
+        validate(&val, &[]);
+
+        for _ in 0..10 {
+            let max_bytes = rng.gen::<usize>() % (footprint + 1);
+            let mut val = arr.clone();
+            val.trim_to_footprint(max_bytes);
+
+            // an empty vector can't go below its fixed size.
+            let max_bytes = max_bytes.max(Vector::<u64>::new().footprint());
+            let slack = mem::size_of::<Node<u64>>() * 2 * arr.root.depth_leafs().0;
+            assert!(
+                val.footprint() <= max_bytes + slack,
+                "{} {}",
+                val.footprint(),
+                max_bytes
+            );
+            validate(&val, &refv[(refv.len() - val.len())..]);
+        }
+    }
+}
+
+#[test]
+fn test_as_unique_mut() {
+    let seed: u64 = random();
+    println!("test_as_unique_mut seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+
+        let shared = arr.clone();
+        match arr.as_unique_mut() {
+            Err(Error::Shared(_, _)) => (),
+            Err(err) => panic!("{}", err),
+            Ok(_) => panic!("expected shared error"),
+        }
+        mem::drop(shared);
+
+        let mut guard = arr.as_unique_mut().unwrap();
+        assert!(guard.get_mut(refv.len()).is_err());
+        for _ in 0..1000 {
+            match rng.gen::<u8>() % 4 {
+                0 => {
+                    let off = rng.gen::<usize>() % (refv.len() + 1);
+                    let val: u64 = rng.gen();
+                    guard.insert_mut(off, val).unwrap();
+                    refv.insert(off, val);
+                }
+                1 if !refv.is_empty() => {
+                    let off = rng.gen::<usize>() % refv.len();
+                    let val: u64 = rng.gen();
+                    assert_eq!(guard.update_mut(off, val).unwrap(), refv[off]);
+                    refv[off] = val;
+                }
+                2 if !refv.is_empty() => {
+                    let off = rng.gen::<usize>() % refv.len();
+                    assert_eq!(guard.remove_mut(off).unwrap(), refv.remove(off));
+                }
+                3 if !refv.is_empty() => {
+                    let off = rng.gen::<usize>() % refv.len();
+                    *guard.get_mut(off).unwrap() += 1;
+                    refv[off] += 1;
+                    assert_eq!(guard.get(off).unwrap(), &refv[off]);
+                }
+                _ => (),
+            }
+            assert_eq!(guard.len(), refv.len());
+        }
+        assert!(arr.verify().is_ok());
+        validate(&arr.rebalance(true).unwrap(), &refv);
+    }
+}
+
+#[test]
+fn test_split_every() {
+    let seed: u64 = random();
+    println!("test_split_every seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&refv, Some(128));
+
+        assert!(arr.split_every(0).is_err());
+        for k in [1, 7, 128, 1000, rng.gen::<usize>() % 2000 + 1].iter() {
+            if *n > 1000 && *k < 7 {
+                continue;
+            }
+            let batches = arr.split_every(*k).unwrap();
+            let refs: Vec<&[u64]> = refv.chunks(*k).collect();
+            assert_eq!(batches.len(), refs.len());
+            for (batch, refb) in batches.iter().zip(refs) {
+                assert_eq!(&Vec::from(batch.clone()), refb);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_from_fn() {
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        for leaf_size in [None, Some(128), Some(1024)].iter() {
+            let arr = Vector::from_fn(*n, |i| (i * i) as u64, *leaf_size);
+            let refv: Vec<u64> = (0..*n).map(|i| (i * i) as u64).collect();
+            validate(&arr, &refv);
+            assert_eq!(arr.leaf_cap, leaf_size.unwrap_or(crate::LEAF_CAP));
+            assert_eq!(arr, Vector::from_slice(&refv, *leaf_size));
+        }
+    }
+}
+
+#[test]
+fn test_ptr_eq() {
+    let seed: u64 = random();
+    println!("test_ptr_eq seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).collect();
+        let arr = Vector::from_slice(&refv, Some(128));
+        let mut val = arr.clone();
+        assert!(arr.ptr_eq(&val) && val.ptr_eq(&arr));
+
+        // same elements, different trees.
+        let other = Vector::from_slice(&refv, Some(128));
+        assert!(!arr.ptr_eq(&other));
+        assert_eq!(arr, other);
+
+        let off = rng.gen::<usize>() % (refv.len() + 1);
+        val.insert(off, 0).unwrap();
+        assert!(!arr.ptr_eq(&val));
+        validate(&arr, &refv);
+    }
+
+    // elements need not be comparable.
+    #[derive(Clone)]
+    struct Opaque(#[allow(dead_code)] usize);
+    let arr = Vector::from_fn(10, Opaque, None);
+    assert!(arr.ptr_eq(&arr.clone()));
+}
+
+#[test]
+fn test_leaf_fill_histogram() {
+    let seed: u64 = random();
+    println!("test_leaf_fill_histogram seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut arr = Vector::from_slice(&(0..*n).collect::<Vec<u64>>(), Some(128));
+        let cap = max_leaf_items::<u64>(128);
+
+        assert!(arr.leaf_fill_histogram(0).is_empty());
+        let histogram = arr.leaf_fill_histogram(4);
+        let n_full = *n as usize / cap;
+        assert_eq!(histogram[3], n_full);
+        assert_eq!(histogram.iter().sum::<usize>(), arr.iter_leaves().count());
+
+        for _ in 0..(*n / 2) {
+            let off = rng.gen::<usize>() % arr.len();
+            arr.remove(off).unwrap();
+        }
+        for buckets in [1, 3, 10].iter() {
+            let histogram = arr.leaf_fill_histogram(*buckets);
+            assert_eq!(histogram.len(), *buckets);
+            let mut refh = vec![0; *buckets];
+            for (_, data) in arr.iter_leaves() {
+                let fill = data.len() as f64 / cap as f64;
+                let bucket = (fill * (*buckets as f64)) as usize;
+                refh[bucket.min(*buckets - 1)] += 1;
+            }
+            assert_eq!(histogram, refh);
+        }
+    }
+}
+
+#[test]
+fn test_merge_sorted_shared() {
+    let seed: u64 = random();
+    println!("test_merge_sorted_shared seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        // interleaved, mostly disjoint and fully disjoint ranges.
+        for spread in [1, 100, 1_000_000].iter() {
+            let mut xs: Vec<u64> = (0..*n)
+                .map(|_| rng.gen::<u64>() % (*n as u64 + 1))
+                .collect();
+            let offset = (*n as u64) * spread / 2;
+            let mut ys: Vec<u64> = (0..(rng.gen::<usize>() % (*n + 1)))
+                .map(|_| rng.gen::<u64>() % (*n as u64 + 1) + offset)
+                .collect();
+            xs.sort();
+            ys.sort();
+
+            let a = Vector::from_slice(&xs, Some(1024));
+            let b = Vector::from_slice(&ys, Some(1024));
+            let arr = a.merge_sorted_shared(&b);
+            let refv = a.merge_by(&b, |x, y| x.cmp(y));
+            assert_eq!(arr, refv);
+            assert!(arr.verify().is_ok());
+            validate(&arr.rebalance(true).unwrap(), &Vec::from(refv));
+
+            let arr = b.merge_sorted_shared(&a);
+            let refv = b.merge_by(&a, |x, y| x.cmp(y));
+            assert_eq!(arr, refv);
+        }
+    }
+
+    // disjoint ranges share all the leaf nodes.
+    let a = Vector::from_slice(&(0..10_000).collect::<Vec<u64>>(), Some(128));
+    let b = Vector::from_slice(&(10_000..20_000).collect::<Vec<u64>>(), Some(128));
+    let arr = b.merge_sorted_shared(&a);
+    let ptrs = |arr: &Vector<u64>| -> Vec<*const u64> {
+        arr.iter_leaves().map(|(_, data)| data.as_ptr()).collect()
+    };
+    let mut refp = ptrs(&a);
+    refp.extend(ptrs(&b));
+    assert_eq!(ptrs(&arr), refp);
+    validate(&arr, &(0..20_000).collect::<Vec<u64>>());
+}
+
+#[test]
+fn test_iter_nth() {
+    let seed: u64 = random();
+    println!("test_iter_nth seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+        let mut refv = refv;
+        // uneven leaf nodes.
+        for _ in 0..(*n / 10) {
+            let off = rng.gen::<usize>() % (arr.len() + 1);
+            arr.insert(off, off as u64).unwrap();
+            refv.insert(off, off as u64);
+        }
+
+        assert_eq!(arr.iter().nth(refv.len()), None);
+        for _ in 0..100 {
+            let (mut iter, mut refi) = (arr.iter(), refv.iter());
+            loop {
+                let k = rng.gen::<usize>() % (refv.len() / 4 + 2);
+                let (item, refitem) = (iter.nth(k), refi.nth(k));
+                assert_eq!(item, refitem);
+                if item.is_none() {
+                    break;
+                }
+                let m = rng.gen::<usize>() % 10;
+                let (items, refitems): (Vec<&u64>, Vec<&u64>) = (
+                    iter.by_ref().take(m).collect(),
+                    refi.by_ref().take(m).collect(),
+                );
+                assert_eq!(items, refitems);
+            }
+            assert_eq!(iter.next(), None);
+
+            let k = rng.gen::<usize>() % (refv.len() + 1);
+            let items: Vec<&u64> = arr.iter().skip(k).collect();
+            assert_eq!(items, refv[k..].iter().collect::<Vec<&u64>>());
+        }
+    }
+}
+
+#[test]
+fn test_push() {
+    let seed: u64 = random();
+    println!("test_push seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+        arr.set_debug_checks(*n <= 1000);
+
+        let mut snapshots = vec![];
+        for i in 0..10_000 {
+            let val: u64 = rng.gen();
+            arr.push(val);
+            refv.push(val);
+            // sharing, either full or partial, shall fall back to cow.
+            match rng.gen::<u8>() % 100 {
+                0 => snapshots.push((arr.clone(), refv.clone())),
+                1 => {
+                    let off = rng.gen::<usize>() % (arr.len() + 1);
+                    snapshots.push((arr.slice(off, arr.len()), refv[off..].to_vec()))
+                }
+                2 => {
+                    let off = rng.gen::<usize>() % arr.len();
+                    let val = rng.gen::<u64>();
+                    arr.update(off, val).unwrap();
+                    refv[off] = val;
+                }
+                _ => (),
+            }
+            if i % 1000 == 0 {
+                assert!(arr.verify().is_ok());
+            }
+        }
+        validate(&arr.rebalance(true).unwrap(), &refv);
+        for (arr, refv) in snapshots.iter() {
+            assert_eq!(arr.iter().copied().collect::<Vec<u64>>(), *refv);
+        }
+    }
+}
+
+#[test]
+fn test_retain_indexed() {
+    let seed: u64 = random();
+    println!("test_retain_indexed seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+
+        let mut seen = vec![];
+        arr.retain_indexed(|off, item| {
+            assert_eq!(*item, refv[off]);
+            seen.push(off);
+            off % 2 == 0
+        });
+        assert_eq!(seen, (0..refv.len()).collect::<Vec<usize>>());
+        let refv: Vec<u64> = refv.into_iter().step_by(2).collect();
+        validate(&arr, &refv);
+
+        let drop: Vec<usize> = (0..refv.len() / 3).map(|_| rng.gen()).collect();
+        let drop: std::collections::HashSet<usize> =
+            drop.into_iter().map(|off| off % refv.len()).collect();
+        arr.retain_indexed(|off, _| !drop.contains(&off));
+        let refv: Vec<u64> = refv
+            .into_iter()
+            .enumerate()
+            .filter_map(|(off, item)| (!drop.contains(&off)).then_some(item))
+            .collect();
+        validate(&arr, &refv);
+    }
+}
+
+#[test]
+fn test_describe_tree() {
+    let arr = Vector::from_slice(&[0_u64; 5], Some(16));
+    let refs = "vector len:5 leaf_cap:16 depth:3 leafs:3
+nodem:5 weight:4
+  nodem:4 weight:2
+    nodez:2
+    nodez:2
+  nodez:1
+";
+    assert_eq!(arr.describe_tree(), refs);
+
+    let arr: Vector<u64> = Vector::new();
+    assert_eq!(
+        arr.describe_tree(),
+        format!(
+            "vector len:0 leaf_cap:{} depth:1 leafs:1\nnodez:0\n",
+            crate::LEAF_CAP
+        )
+    );
+
+    for n in [1, 10, 1000, 100_000].iter() {
+        let arr = Vector::from_slice(&(0..*n).collect::<Vec<u64>>(), Some(128));
+        let desc = arr.describe_tree();
+        let (_, n_leafs) = arr.root.depth_leafs();
+        assert_eq!(
+            desc.lines()
+                .filter(|l| l.trim_start().starts_with("nodez"))
+                .count(),
+            n_leafs
+        );
+        assert_eq!(desc.lines().count(), 1 + n_leafs * 2 - 1);
+    }
+}
+
+#[test]
+fn test_split_off_footprint() {
+    let seed: u64 = random();
+    println!("test_split_off_footprint seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // multiple of leaf node size, so that from_slice leaves no slack.
+    let n = 1 << 20;
+    let arr = Vector::from_slice(&(0..n).collect::<Vec<u64>>(), Some(1024));
+    let mut pieces = vec![arr];
+    for _ in 0..10_000 {
+        let i = rng.gen::<usize>() % pieces.len();
+        let off = rng.gen::<usize>() % (pieces[i].len() + 1);
+        let tail = pieces[i].split_off(off).unwrap();
+        pieces.push(tail);
+    }
+
+    // every piece holds at most one empty leaf node, and the footprint
+    // stays proportional to live elements.
+    let (mut n_leafs, mut n_empty, mut footprint) = (0, 0, 0);
+    for piece in pieces.iter() {
+        let report = piece.memory_report();
+        assert!(piece.verify().is_ok());
+        assert_eq!(report.data_bytes, report.data_capacity_bytes);
+        n_leafs += report.n_leafs;
+        n_empty += report.n_leafs - piece.iter_leaves().count();
+        footprint += piece.footprint() - mem::size_of::<Vector<u64>>();
+    }
+    println!(
+        "test_split_off_footprint leafs:{} empty:{}",
+        n_leafs, n_empty
+    );
+    assert!(n_empty <= pieces.len(), "{} {}", n_empty, pieces.len());
+    assert!(footprint < (n as usize) * 8 * 3 / 2, "{}", footprint);
+
+    pieces.sort_by_key(|piece| piece.get(0).ok().copied());
+    let items: Vec<u64> = pieces
+        .iter()
+        .flat_map(|piece| piece.iter().copied())
+        .collect();
+    assert_eq!(items, (0..n).collect::<Vec<u64>>());
+}
+
+#[test]
+fn test_checked_len() {
+    let seed: u64 = random();
+    println!("test_checked_len seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut arr = Vector::from_slice(&(0..*n).collect::<Vec<u64>>(), Some(128));
+        assert_eq!(arr.checked_len().unwrap(), *n as usize);
+
+        for _ in 0..10 {
+            let other =
+                Vector::from_slice(&vec![0; rng.gen::<usize>() % 1000], Some(128));
+            arr.append(other);
+            assert_eq!(arr.checked_len().unwrap(), arr.len());
+        }
+
+        arr.len += 1;
+        match arr.checked_len() {
+            Err(Error::Fatal(_, _)) => (),
+            res => panic!("unexpected {:?}", res),
+        }
+    }
+}
+
+#[test]
+fn test_rev_iter() {
+    let seed: u64 = random();
+    println!("test_rev_iter seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+        // uneven and empty leaf nodes.
+        for _ in 0..(*n / 10) {
+            let off = rng.gen::<usize>() % (arr.len() + 1);
+            arr.insert(off, off as u64).unwrap();
+            refv.insert(off, off as u64);
+            let off = rng.gen::<usize>() % arr.len();
+            arr.remove(off).unwrap();
+            refv.remove(off);
+        }
+        if !refv.is_empty() {
+            let off = rng.gen::<usize>() % refv.len();
+            let tail = arr.split_off(off).unwrap();
+            arr.append_no_rebalance(tail);
+        }
+
+        let items: Vec<&u64> = arr.rev_iter().collect();
+        let mut refs: Vec<&u64> = arr.iter().collect();
+        refs.reverse();
+        assert_eq!(items, refs);
+        assert_eq!(items, refv.iter().rev().collect::<Vec<&u64>>());
+    }
+}
+
+#[test]
+fn test_insert_depth() {
+    let seed: u64 = random();
+    println!("test_insert_depth seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    fn path_depth(node: &Node<u64>, off: usize) -> usize {
+        match node {
+            Node::M { weight, left, .. } if off < *weight => 1 + path_depth(left, off),
+            Node::M { weight, right, .. } => 1 + path_depth(right, off - *weight),
+            Node::Z { .. } => 1,
+        }
+    }
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        for auto_rebalance in [false, true].iter() {
+            let mut refv: Vec<u64> = (0..*n).collect();
+            let mut arr = Vector::from_slice(&refv, Some(128));
+            arr.set_auto_rebalance(*auto_rebalance);
+
+            for i in 0..1000 {
+                let off = rng.gen::<usize>() % (arr.len() + 1);
+                let val: u64 = rng.gen();
+                let depth = match i % 2 {
+                    0 => arr.insert_depth(off, val).unwrap(),
+                    _ => arr.insert_mut_depth(off, val).unwrap(),
+                };
+                refv.insert(off, val);
+
+                if *auto_rebalance {
+                    assert!(depth <= arr.root.depth_leafs().0 + 1);
+                } else {
+                    assert_eq!(depth, path_depth(&arr.root, off), "off:{}", off);
+                }
+            }
+            assert!(arr.insert_depth(arr.len() + 1, 0).is_err());
+            assert!(arr.insert_mut_depth(arr.len() + 1, 0).is_err());
+            validate(&arr.rebalance(true).unwrap(), &refv);
+        }
+    }
+
+    // inserting at the head skews the tree until it is auto-rebalanced.
+    let count = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let c = std::sync::Arc::clone(&count);
+    let mut arr: Vector<u64> = Vector::new();
+    arr.set_leaf_size(128);
+    arr.set_on_rebalance(Some(Box::new(move |_| {
+        c.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    })));
+    for i in 0..100_000 {
+        let depth = match i % 2 {
+            0 => arr.insert_depth(0, i).unwrap(),
+            _ => arr.insert_mut_depth(0, i).unwrap(),
+        };
+        assert_eq!(depth, path_depth(&arr.root, 0), "i:{}", i);
+    }
+    assert!(count.load(std::sync::atomic::Ordering::SeqCst) > 0);
+}
+
+#[test]
+fn test_cloned_iter() {
+    let seed: u64 = random();
+    println!("test_cloned_iter seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<String> = (0..*n).map(|_| rng.gen::<u64>().to_string()).collect();
+        let arr = Vector::from_slice(&refv, Some(128));
+
+        let items: Vec<String> = arr.cloned_iter().collect();
+        assert_eq!(items, arr.iter().cloned().collect::<Vec<String>>());
+        assert_eq!(items, refv);
+        validate(&arr, &refv);
+    }
+}
+
+#[test]
+fn test_range_fold() {
+    let seed: u64 = random();
+    println!("test_range_fold seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>() % 1000).collect();
+        let arr = Vector::from_slice(&refv, Some(128));
+        let sum = |data: &[u64]| -> u64 { data.iter().sum() };
+        let add = |a: u64, b: u64| a + b;
+
+        assert_eq!(
+            arr.range_fold(.., sum, add).unwrap(),
+            arr.reduce_balanced(sum, add)
+        );
+        assert!(arr.range_fold(0..(refv.len() + 1), sum, add).is_err());
+        for _ in 0..100 {
+            let a = rng.gen::<usize>() % (refv.len() + 1);
+            let b = rng.gen::<usize>() % (refv.len() + 1);
+            let (start, end) = (a.min(b), a.max(b));
+
+            let val = arr.range_fold(start..end, sum, add).unwrap();
+            match start == end {
+                true => assert_eq!(val, None),
+                false => assert_eq!(val, Some(refv[start..end].iter().sum::<u64>())),
+            }
+
+            // leaf nodes are visited in order, with only the overlap.
+            let items = arr
+                .range_fold(
+                    start..end,
+                    |data| data.to_vec(),
+                    |mut a, b| {
+                        a.extend(b);
+                        a
+                    },
+                )
+                .unwrap()
+                .unwrap_or_default();
+            assert_eq!(items, refv[start..end]);
+        }
+    }
+}
+
+#[test]
+fn test_update_many() {
+    let seed: u64 = random();
+    println!("test_update_many seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+
+        assert!(arr.update_many(&[]).unwrap().is_empty());
+        assert!(arr.update_many(&[(refv.len(), 0)]).is_err());
+        if refv.len() > 1 {
+            assert!(arr.update_many(&[(1, 0), (0, 0)]).is_err());
+        }
+        validate(&arr, &refv);
+
+        for i in 0..20 {
+            let k = rng.gen::<usize>() % (refv.len() / 10 + 2);
+            let mut updates: Vec<(usize, u64)> = (0..k)
+                .filter(|_| !refv.is_empty())
+                .map(|_| (rng.gen::<usize>() % refv.len(), rng.gen()))
+                .collect();
+            updates.sort_by_key(|(off, _)| *off);
+
+            // every other round, the vector is shared with a snapshot.
+            let snapshot = (i % 2 == 0).then(|| (arr.clone(), refv.clone()));
+            let leafs: Vec<(usize, *const u64)> = arr
+                .iter_leaves()
+                .map(|(off, data)| (off, data.as_ptr()))
+                .collect();
+
+            let olds = arr.update_many(&updates).unwrap();
+            let mut refolds = vec![];
+            for (off, val) in updates.iter() {
+                refolds.push(refv[*off]);
+                refv[*off] = *val;
+            }
+            assert_eq!(olds, refolds);
+            assert!(arr.iter().eq(refv.iter()));
+
+            // untouched leaf nodes are retained as is.
+            for ((off, ptr), (_, data)) in leafs.iter().zip(arr.iter_leaves()) {
+                let end = off + data.len();
+                let i = updates.partition_point(|(i, _)| i < off);
+                let touched = updates.get(i).map(|(i, _)| *i < end).unwrap_or(false);
+                if !touched || snapshot.is_none() {
+                    assert_eq!(*ptr, data.as_ptr());
+                }
+            }
+            if let Some((arr, refv)) = snapshot {
+                assert!(arr.iter().eq(refv.iter()));
+            }
+        }
+        validate(&arr, &refv);
+    }
+}
+
+#[test]
+fn test_try_from_array() {
+    let seed: u64 = random();
+    println!("test_try_from_array seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let refv: Vec<u64> = (0..1000).map(|_| rng.gen()).collect();
+    let arr = Vector::from_slice(&refv, Some(128));
+
+    let val: [u64; 1000] = <[u64; 1000]>::try_from(arr.clone()).unwrap();
+    assert_eq!(&val[..], &refv[..]);
+
+    let val: [u64; 0] = <[u64; 0]>::try_from(Vector::new()).unwrap();
+    assert!(val.is_empty());
+
+    let items = ["a".to_string(), "b".to_string(), "c".to_string()];
+    let val = <[String; 3]>::try_from(Vector::from_slice(&items, None)).unwrap();
+    assert_eq!(val, ["a", "b", "c"]);
+
+    match <[u64; 999]>::try_from(arr.clone()) {
+        Err(Error::InvalidInput(_, _)) => (),
+        res => panic!("unexpected {:?}", res.map(|_| ())),
+    }
+    assert!(<[u64; 1001]>::try_from(arr).is_err());
+}
+
+#[test]
+fn test_coalesce() {
+    let seed: u64 = random();
+    println!("test_coalesce seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // (style, length) spans, adjacent spans with same style are merged.
+    let merge = |a: (u8, u64), b: (u8, u64)| match a.0 == b.0 {
+        true => Ok((a.0, a.1 + b.1)),
+        false => Err((a, b)),
+    };
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<(u8, u64)> = (0..*n)
+            .map(|_| (rng.gen::<u8>() % 3, rng.gen::<u64>() % 100))
+            .collect();
+        let arr = Vector::from_slice(&refv, Some(128));
+
+        let mut refc: Vec<(u8, u64)> = vec![];
+        for item in refv.iter() {
+            match refc.last_mut() {
+                Some(last) if last.0 == item.0 => last.1 += item.1,
+                _ => refc.push(*item),
+            }
+        }
+
+        let val = arr.coalesce(merge);
+        validate(&val, &refc);
+        assert_eq!(
+            val.iter().map(|x| x.1).sum::<u64>(),
+            refv.iter().map(|x| x.1).sum::<u64>()
+        );
+    }
+
+    // all elements in a single style merge into one, across leaf nodes.
+    let arr = Vector::from_slice(&vec![(0_u8, 1_u64); 10_000], Some(128));
+    validate(&arr.coalesce(merge), &[(0, 10_000)]);
+}
+
+#[test]
+fn test_binary_search_empty_leafs() {
+    let seed: u64 = random();
+    println!("test_binary_search_empty_leafs seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut refv: Vec<u64> = (0..100_000).map(|_| rng.gen::<u64>() % 1_000_000).collect();
+    refv.sort_unstable();
+    let mut arr = Vector::from_slice(&refv, Some(128));
+
+    // in-place removes leave runs of empty leaf nodes behind.
+    let (start, n) = (10_000, 1000 + rng.gen::<usize>() % 80_000);
+    for _ in 0..n {
+        assert_eq!(arr.remove_mut(start).unwrap(), refv.remove(start));
+    }
+    assert!(arr.iter_leaves().count() < arr.memory_report().n_leafs);
+
+    for _ in 0..1000 {
+        let key = rng.gen::<u64>() % 1_000_001;
+        let res = arr.binary_search_by_key(&key, |x| *x);
+        match res {
+            Ok(off) => assert_eq!(refv[off], key),
+            Err(off) => assert_eq!(res, refv.binary_search(&key).map(|_| off)),
+        }
+        let lo = refv.partition_point(|x| *x < key);
+        let hi = refv.partition_point(|x| *x <= key);
+        assert_eq!(arr.equal_range_by_key(&key, |x| *x), lo..hi);
+    }
+}
+
+#[test]
+fn test_binary_search_by_key() {
+    let seed: u64 = random();
+    println!("test_binary_search_by_key seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // not Ord, sorted by timestamp.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Row {
+        timestamp: u64,
+        value: f64,
+    }
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut timestamps: Vec<u64> =
+            (0..*n).map(|_| rng.gen::<u64>() % 1_000_000).collect();
+        timestamps.sort_unstable();
+        let refv: Vec<Row> = timestamps
+            .iter()
+            .map(|timestamp| Row {
+                timestamp: *timestamp,
+                value: rng.gen(),
+            })
+            .collect();
+        let arr = Vector::from_slice(&refv, Some(128));
+
+        for _ in 0..1000 {
+            let key = match rng.gen::<bool>() && !refv.is_empty() {
+                true => refv[rng.gen::<usize>() % refv.len()].timestamp,
+                false => rng.gen::<u64>() % 1_000_001,
+            };
+            let res = arr.binary_search_by_key(&key, |row| row.timestamp);
+            match res {
+                Ok(off) => assert_eq!(refv[off].timestamp, key),
+                Err(off) => {
+                    assert!(off == 0 || refv[off - 1].timestamp < key);
+                    assert!(off == refv.len() || refv[off].timestamp > key);
+                }
+            }
+            let refr = refv.binary_search_by_key(&key, |row| row.timestamp);
+            assert_eq!(res.is_ok(), refr.is_ok());
+            if res.is_err() {
+                assert_eq!(res, refr);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_retain() {
+    let seed: u64 = random();
+    println!("test_retain seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>() % 1000).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+
+        let k = rng.gen::<u64>() % 1000;
+        let removed = arr.retain(|x| *x < k);
+        let before = refv.len();
+        refv.retain(|x| *x < k);
+        assert_eq!(removed, before - refv.len());
+        validate(&arr, &refv);
+
+        let removed = arr.retain_mut(|x| {
+            *x += 1;
+            *x % 2 == 0
+        });
+        let before = refv.len();
+        refv.retain_mut(|x| {
+            *x += 1;
+            *x % 2 == 0
+        });
+        assert_eq!(removed, before - refv.len());
+        validate(&arr, &refv);
+
+        assert_eq!(arr.retain(|_| true), 0);
+        assert_eq!(arr.retain(|_| false), refv.len());
+        assert!(arr.is_empty());
+    }
+}
+
+#[test]
+fn test_halve() {
+    let seed: u64 = random();
+    println!("test_halve seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+        for _ in 0..(*n / 10) {
+            let off = rng.gen::<usize>() % (arr.len() + 1);
+            arr.insert(off, off as u64).unwrap();
+            refv.insert(off, off as u64);
+        }
+        let leafs: Vec<*const u64> = arr.iter_leaves().map(|(_, d)| d.as_ptr()).collect();
+
+        let (a, b) = arr.halve();
+        assert_eq!(a.len() + b.len(), refv.len());
+        validate(&a, &refv[..a.len()]);
+        validate(&b, &refv[a.len()..]);
+
+        // halves share all the leaf nodes, and divide at a leaf boundary
+        // closest to the mid point.
+        let mut ptrs: Vec<*const u64> =
+            a.iter_leaves().map(|(_, d)| d.as_ptr()).collect();
+        ptrs.extend(b.iter_leaves().map(|(_, d)| d.as_ptr()));
+        assert_eq!(ptrs, leafs);
+        let mid = refv.len() / 2;
+        for (off, data) in arr.iter_leaves() {
+            for bound in [off, off + data.len()].iter() {
+                let (x, y) = (
+                    bound.max(&mid) - bound.min(&mid),
+                    a.len().max(mid) - a.len().min(mid),
+                );
+                assert!(y <= x, "{} {} {}", bound, a.len(), mid);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_map_cached() {
+    let seed: u64 = random();
+    println!("test_map_cached seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>() % 1000).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+        let mut cache = MapCache::default();
+
+        let mut n_calls = 0;
+        let mut f = |data: &[u64]| {
+            n_calls += 1;
+            data.iter().map(|x| x.to_string()).collect::<Vec<String>>()
+        };
+        let val = arr.map_cached(&mut f, &mut cache);
+        let refs: Vec<String> = refv.iter().map(|x| x.to_string()).collect();
+        validate(&val, &refs);
+        let n_leafs = arr.iter_leaves().count();
+        assert_eq!(cache.len(), n_leafs);
+
+        // mapping again is served from the cache.
+        let val = arr.map_cached(&mut f, &mut cache);
+        validate(&val, &refs);
+        assert_eq!(cache.len(), n_leafs);
+
+        // new version shares all but the updated leaf nodes.
+        let k = (*n / 100) as usize;
+        for _ in 0..k {
+            let off = rng.gen::<usize>() % refv.len();
+            arr.update(off, 0).unwrap();
+            refv[off] = 0;
+        }
+        let val = arr.map_cached(&mut f, &mut cache);
+        let refs: Vec<String> = refv.iter().map(|x| x.to_string()).collect();
+        validate(&val, &refs);
+        assert!(cache.len() <= n_leafs + k);
+
+        // leaf nodes can map to any number of elements.
+        let val = arr.map_cached(
+            |data: &[u64]| data.iter().filter(|x| **x % 2 == 0).copied().collect(),
+            &mut MapCache::default(),
+        );
+        let refe: Vec<u64> = refv.iter().filter(|x| **x % 2 == 0).copied().collect();
+        assert!(val.iter().eq(refe.iter()));
+
+        // old versions are not kept alive by the cache.
+        let n_cached = cache.len();
+        arr = arr.rebalance(true).unwrap();
+        let val = arr.map_cached(&mut f, &mut cache);
+        validate(&val, &refs);
+        cache.gc();
+        assert!(cache.len() <= n_cached, "{} {}", cache.len(), n_cached);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        arr.update_mut(0, 0).ok();
+    }
+}
+
+#[test]
+fn test_from_slice_slack() {
+    let seed: u64 = random();
+    println!("test_from_slice_slack seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+
+        // only the partially filled leaf node has slack.
+        let report = arr.memory_report();
+        let tail = refv.len() % 16;
+        let slack = report.data_capacity_bytes - report.data_bytes;
+        match tail {
+            0 => assert_eq!(slack, 0),
+            _ => assert_eq!(slack, (std::cmp::min(16, tail + tail / 4 + 1) - tail) * 8),
+        }
+
+        for _ in 0..(tail / 4 + 1) {
+            let off = refv.len() - (rng.gen::<usize>() % (tail + 1));
+            arr.insert_mut(off, off as u64).unwrap();
+            refv.insert(off, off as u64);
+        }
+        validate(&arr, &refv);
+    }
+}
+
+#[test]
+fn test_cow_ops() {
+    let seed: u64 = random();
+    println!("test_cow_ops seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+        let mut snaps = vec![];
+
+        for i in 0..1000 {
+            // keep a few versions around, so that parts of the tree are shared.
+            if i % 100 == 0 {
+                snaps.push((arr.clone(), refv.clone()));
+            }
+            match rng.gen::<u8>() % 3 {
+                0 => {
+                    let off = rng.gen::<usize>() % (refv.len() + 1);
+                    let val = rng.gen::<u64>();
+                    arr.insert_cow(off, val).unwrap();
+                    refv.insert(off, val);
+                }
+                1 if !refv.is_empty() => {
+                    let off = rng.gen::<usize>() % refv.len();
+                    assert_eq!(arr.remove_cow(off).unwrap(), refv.remove(off));
+                }
+                _ if !refv.is_empty() => {
+                    let off = rng.gen::<usize>() % refv.len();
+                    let val = rng.gen::<u64>();
+                    let old = arr.update_cow(off, val).unwrap();
+                    assert_eq!(old, refv[off]);
+                    refv[off] = val;
+                }
+                _ => (),
+            }
+        }
+
+        let off = refv.len() + 1;
+        assert!(arr.insert_cow(off, 0).is_err());
+        assert!(arr.remove_cow(refv.len()).is_err());
+        assert!(arr.update_cow(refv.len(), 0).is_err());
+
+        validate(&arr.rebalance(true).unwrap(), &refv);
+        for (arr, refv) in snaps.iter() {
+            validate(&arr.rebalance(true).unwrap(), refv);
+        }
+    }
+}
+
+#[test]
+fn test_flatten() {
+    let seed: u64 = random();
+    println!("test_flatten seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000].iter() {
+        let mut refv: Vec<u64> = vec![];
+        let mut inners: Vec<Vector<u64>> = vec![];
+        for _ in 0..*n {
+            let m = match rng.gen::<u8>() % 4 {
+                0 => rng.gen::<usize>() % 2000,
+                _ => rng.gen::<usize>() % 20,
+            };
+            let vals: Vec<u64> = (0..m).map(|_| rng.gen::<u64>()).collect();
+            let leaf_cap = match rng.gen::<u8>() % 4 {
+                0 => 256,
+                _ => 128,
+            };
+            refv.extend_from_slice(&vals);
+            inners.push(Vector::from_slice(&vals, Some(leaf_cap)));
+        }
+        let arr = Vector::from_slice(&inners, Some(128));
+        let flat = arr.clone().flatten();
+        validate(&flat, &refv);
+        assert_eq!(flat.leaf_cap, 128);
+
+        // half full leaf nodes, of inner vectors with same leaf node size,
+        // are shared.
+        let flat_leafs = Node::collect_leaf_nodes(Ref::clone(&flat.root), false, 0);
+        for inner in inners.iter().filter(|inner| inner.leaf_cap == 128) {
+            for leaf in Node::collect_leaf_nodes(Ref::clone(&inner.root), false, 0) {
+                let shared = flat_leafs.iter().any(|x| Ref::ptr_eq(x, &leaf));
+                assert_eq!(shared, leaf.len() >= 8, "{}", leaf.len());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_index_fail_len() {
+    let mut arr = Vector::from_slice(&(0..100_u64).collect::<Vec<u64>>(), None);
+
+    let suffix = "out of bounds for length 100";
+    assert!(arr.get(150).unwrap_err().to_string().ends_with(suffix));
+    assert!(arr
+        .insert(150, 0)
+        .unwrap_err()
+        .to_string()
+        .ends_with(suffix));
+    assert!(arr
+        .update(150, 0)
+        .unwrap_err()
+        .to_string()
+        .ends_with(suffix));
+    assert!(arr.remove(150).unwrap_err().to_string().ends_with(suffix));
+    assert!(arr
+        .split_off(150)
+        .unwrap_err()
+        .to_string()
+        .ends_with(suffix));
+    let res = arr.range_fold(10..150, |data| data.len(), |a, b| a + b);
+    assert!(res.unwrap_err().to_string().ends_with(suffix));
+
+    match arr.get(100) {
+        Err(Error::IndexFail(_, msg)) => {
+            assert_eq!(msg, "index 100 out of bounds for length 100")
+        }
+        res => panic!("unexpected {:?}", res),
+    }
+}
+
+#[test]
+fn test_append_any() {
+    let seed: u64 = random();
+    println!("test_append_any seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let mut refv: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>()).collect();
+        let leaf_cap = [128, crate::LEAF_CAP][rng.gen::<usize>() % 2];
+        let mut arr = Vector::from_slice(&refv, Some(leaf_cap));
+
+        for _ in 0..10 {
+            let m = rng.gen::<usize>() % (*n + 1);
+            let vals: Vec<u64> = (0..m).map(|_| rng.gen::<u64>()).collect();
+            refv.extend_from_slice(&vals);
+            match rng.gen::<u8>() % 3 {
+                0 => arr.append_any(vals),
+                1 => arr.append_any(vals.as_slice()),
+                _ => arr.append_any(Vector::from_slice(&vals, Some(leaf_cap))),
+            }
+        }
+        assert_eq!(arr.leaf_cap, leaf_cap);
+        validate(&arr.rebalance(true).unwrap(), &refv);
+
+        let arr: Vector<u64> = refv.clone().into();
+        validate(&arr, &refv);
+        let arr: Vector<u64> = refv.as_slice().into();
+        validate(&arr, &refv);
+    }
+}
+
+#[test]
+fn test_iter_clone() {
+    let seed: u64 = random();
+    println!("test_iter_clone seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>()).collect();
+        let arr = Vector::from_slice(&refv, Some(128));
+        let k = rng.gen::<usize>() % (*n + 1);
+
+        let mut iter = arr.iter();
+        assert_eq!(iter.by_ref().take(k).count(), k);
+        let mut peek = iter.clone();
+        assert_eq!(peek.next(), refv.get(k));
+        assert!(iter.eq(refv[k..].iter()));
+        assert!(peek.eq(refv[(k + 1).min(*n)..].iter()));
+
+        let mut iter = arr.rev_iter();
+        assert_eq!(iter.by_ref().take(k).count(), k);
+        let peek = iter.clone();
+        assert!(iter.eq(refv[..*n - k].iter().rev()));
+        assert!(peek.eq(refv[..*n - k].iter().rev()));
+
+        // shared and uniquely owned leaf nodes.
+        for arr in [arr.clone(), Vector::from_slice(&refv, Some(128))] {
+            let mut iter = arr.into_iter();
+            assert_eq!(iter.by_ref().take(k).count(), k);
+            let peek = iter.clone();
+            assert!(iter.eq(refv[k..].iter().copied()));
+            assert!(peek.eq(refv[k..].iter().copied()));
+        }
+    }
+}
+
+#[test]
+fn test_windows_ref() {
+    let seed: u64 = random();
+    println!("test_windows_ref seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in [0, 1, 10, 1000, 100_000].iter() {
+        let refv: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>()).collect();
+        let mut arr = Vector::from_slice(&refv, Some(128));
+        if rng.gen::<bool>() {
+            // fragment the leaf nodes.
+            arr.insert(*n / 2, 0).unwrap();
+            arr.remove(*n / 2).unwrap();
+        }
+
+        assert!(arr.windows_ref(0).is_err());
+
+        for size in [1, 2, 7, 16, 17].iter() {
+            let iter = arr.windows_ref(*size).unwrap();
+            assert_eq!(iter.size_hint().0, refv.windows(*size).count());
+
+            let (mut n_borrowed, mut n_owned) = (0, 0);
+            for (window, refw) in iter.zip(refv.windows(*size)) {
+                match window {
+                    WindowRef::Borrowed(window) => {
+                        n_borrowed += 1;
+                        assert_eq!(window, refw);
+                    }
+                    WindowRef::Owned(window) => {
+                        n_owned += 1;
+                        assert!(window.iter().eq(refw.iter()));
+                    }
+                }
+            }
+            assert_eq!(n_borrowed + n_owned, refv.windows(*size).count());
+            // every leaf boundary is straddled by `size-1` windows.
+            let n_leafs = arr.iter_leaves().count();
+            assert!(n_owned <= n_leafs.saturating_sub(1) * (size - 1));
+        }
+    }
+}
+
+#[test]
+fn test_merkle_root_encoding() {
+    use sha2::Digest;
+
+    // single leaf node, with usize items hashed as 8-byte little-endian.
+    let arr = Vector::from_slice(&[1_usize, 0x0102], None);
+    let mut hasher = sha2::Sha256::new();
+    hasher.update([0_u8]);
+    hasher.update(2_u64.to_le_bytes());
+    hasher.update(1_u64.to_le_bytes());
+    hasher.update(0x0102_u64.to_le_bytes());
+    let root: [u8; 32] = hasher.finalize().into();
+    assert_eq!(arr.merkle_root(), root);
+
+    // mixed width integers, within a tuple.
+    let arr = Vector::from_slice(&[(1_u16, -1_i32)], None);
+    let mut hasher = sha2::Sha256::new();
+    hasher.update([0_u8]);
+    hasher.update(1_u64.to_le_bytes());
+    hasher.update(1_u16.to_le_bytes());
+    hasher.update((-1_i32).to_le_bytes());
+    let root: [u8; 32] = hasher.finalize().into();
+    assert_eq!(arr.merkle_root(), root);
+}
@@ -1,14 +1,25 @@
 //! Module implement persistent array, faster but not thread safe.
 
 use std::rc::Rc as Ref;
+use std::rc::Weak;
 
 #[path = "./ppar.rs"]
 mod ppar;
 
-pub use self::ppar::{IntoIter, Iter, Vector};
+#[cfg(any(feature = "serde", test))]
+pub use self::ppar::LeafInterner;
+#[cfg(any(feature = "ipld", test))]
+pub use self::ppar::MerkleCache;
+pub use self::ppar::{
+    ChunksExact, IntoIter, Iter, MapCache, MemoryReport, RebalanceEvent, RevIter,
+    UniqueMut, Vector, WindowRef, WindowsRef,
+};
 #[cfg(test)]
 pub use ppar::validate;
 
+/// Callback type for `Vector::set_on_rebalance`.
+pub type RebalanceFn = dyn Fn(RebalanceEvent);
+
 impl<T> Vector<T>
 where
     T: Clone,